@@ -2,3 +2,40 @@ pub mod processor;
 pub mod writer;
 
 pub use assembly::*;
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use utils::i18n::{Lang, Message};
+use utils::io::{file_exists, read_input, Source};
+use utils::report::MessageFormat;
+use utils::Executor;
+
+use crate::{processor::process, writer::print};
+
+#[derive(Parser)]
+#[command(author, version, about = Message::HelpAssemble.to_string(), long_about = None)]
+pub struct Args {
+    #[arg(short, long, value_parser = file_exists, help = Message::HelpInputOrStdin.to_string())]
+    pub input: Option<PathBuf>,
+    #[arg(short = 'g', long, help = Message::HelpDebug.to_string())]
+    pub debug: bool,
+    #[arg(long, value_enum, default_value_t, help = Message::HelpMessageFormat.to_string())]
+    pub message_format: MessageFormat,
+    // Read before parsing by `Lang::detect`, so that help is translated as well
+    #[arg(long, value_enum, help = Message::HelpLang.to_string())]
+    pub lang: Option<Lang>,
+}
+
+impl Executor for Args {
+    fn execute(&self) {
+        let (name, program) = read_input(self.input.as_ref());
+        let process_result = process(&program);
+        print(
+            Source::new(&name, &program),
+            process_result,
+            self.debug,
+            self.message_format,
+        );
+    }
+}
@@ -1,19 +1,11 @@
-use std::io;
+use clap::Parser;
+use utils::i18n::{set_lang, Lang};
+use utils::Executor;
 
-use mvn_assembler::processor::process;
-use mvn_assembler::writer::print;
+use mvn_assembler::Args;
 
 fn main() {
-    let program = io::stdin()
-        .lines()
-        .map(|result| result.expect("unable to read from stdin"))
-        .reduce(|mut acc, result| {
-            acc.push('\n');
-            acc.push_str(&result);
-            acc
-        });
-    if let Some(program) = program {
-        let validator_output = process(&program);
-        print(&program, validator_output);
-    }
+    set_lang(Lang::detect(std::env::args()));
+    let args = Args::parse();
+    args.execute();
 }
@@ -86,7 +86,7 @@ impl<'a> AddressedProgram<'a> {
                 _ => (),
             },
             Instruction::Relational(mneumonic) => match mneumonic {
-                RelationalMneumonic::Export => exported = true,
+                RelationalMneumonic::Export | RelationalMneumonic::WeakExport => exported = true,
                 RelationalMneumonic::Import => imported = true,
             },
         }
@@ -128,13 +128,13 @@ impl<'a> AddressedProgram<'a> {
         let mut label_vector: Vec<(Label, Address)> = Vec::new();
         for AddressedLine { address, line } in &self.lines {
             if let Some(label) = &line.label {
-                label_vector.push((label.value.clone(), address.clone()));
+                label_vector.push((label.value, address.clone()));
             } else if let Instruction::Relational(RelationalMneumonic::Import) =
                 &line.operation.instruction.value
             {
                 if let Operand::Symbolic(label) = &line.operation.operand.value {
                     label_vector.push((
-                        label.clone(),
+                        *label,
                         Address {
                             imported: true,
                             position: address.position,
@@ -648,21 +648,21 @@ mod tests {
         );
         let expected = LabelMap::from([
             (
-                Label("TEST00"),
+                Label::new("TEST00"),
                 Address {
                     position: 0x0,
                     ..Default::default()
                 },
             ),
             (
-                Label("TEST01"),
+                Label::new("TEST01"),
                 Address {
                     position: 0x2,
                     ..Default::default()
                 },
             ),
             (
-                Label("TEST10"),
+                Label::new("TEST10"),
                 Address {
                     position: 0x100,
                     relocatable: false,
@@ -670,7 +670,7 @@ mod tests {
                 },
             ),
             (
-                Label("TEST11"),
+                Label::new("TEST11"),
                 Address {
                     position: 0x122,
                     relocatable: false,
@@ -678,7 +678,7 @@ mod tests {
                 },
             ),
             (
-                Label("TEST20"),
+                Label::new("TEST20"),
                 Address {
                     position: 0x200,
                     relocatable: true,
@@ -706,7 +706,7 @@ mod tests {
         );
         let expected = LabelMap::from([
             (
-                Label("IMPORT0"),
+                Label::new("IMPORT0"),
                 Address {
                     position: 0x0,
                     imported: true,
@@ -714,7 +714,7 @@ mod tests {
                 },
             ),
             (
-                Label("IMPORT1"),
+                Label::new("IMPORT1"),
                 Address {
                     position: 0x1,
                     imported: true,
@@ -722,21 +722,21 @@ mod tests {
                 },
             ),
             (
-                Label("NORMAL"),
+                Label::new("NORMAL"),
                 Address {
                     position: 0x0,
                     ..Default::default()
                 },
             ),
             (
-                Label("EXPORT0"),
+                Label::new("EXPORT0"),
                 Address {
                     position: 0x2,
                     ..Default::default()
                 },
             ),
             (
-                Label("EXPORT1"),
+                Label::new("EXPORT1"),
                 Address {
                     position: 0x4,
                     ..Default::default()
@@ -760,7 +760,7 @@ mod tests {
             .1,
         );
         let expected = LabelMap::from([(
-            Label("IMPORT"),
+            Label::new("IMPORT"),
             Address {
                 position: 0,
                 relocatable: false,
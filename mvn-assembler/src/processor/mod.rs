@@ -1,4 +1,4 @@
-pub(crate) mod address;
+pub mod address;
 mod validator;
 
 use nom;
@@ -11,10 +11,10 @@ use crate::types::Program;
 
 use validator::validate;
 
-pub fn process(program: &str) -> Result<(AddressedProgram, LabelMap), MvnReportError> {
+pub fn process(program: &str) -> Result<(AddressedProgram<'_>, LabelMap<'_>), Vec<MvnReportError>> {
     let parse_result = Program::parse_assembler(program.into());
     let (_, program) = parse_result.map_err(|e| match e {
-        nom::Err::Error(e) | nom::Err::Failure(e) => MvnReportError::from(e),
+        nom::Err::Error(e) | nom::Err::Failure(e) => vec![MvnReportError::from(e)],
         nom::Err::Incomplete(e) => panic!("Unhandled error `{e:?}` occured"),
     })?;
     let addressed_program = AddressedProgram::process(program);
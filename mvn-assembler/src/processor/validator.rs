@@ -1,4 +1,8 @@
-use crate::types::{mneumonic, Instruction, Line, Operand};
+use utils::code::ErrorCode;
+use utils::i18n::Message;
+use utils::suggestion;
+
+use crate::types::{mneumonic, Instruction, Label, Line, Operand};
 
 use crate::processor::address::{Address, AddressedProgram, LabelMap};
 
@@ -6,15 +10,24 @@ use super::MvnReportError;
 
 type ValidatorResult<'a> = Result<(), MvnReportError>;
 
+// Lines are validated independently so every faulty line gets reported
 pub fn validate<'a, 'b>(
     program: &'a AddressedProgram<'b>,
     label_map: &'a LabelMap<'b>,
-) -> ValidatorResult<'b> {
-    for line in program.lines.iter() {
-        let validator = LineValidator::new(&line.line, &line.address, label_map);
-        validator.validate()?;
+) -> Result<(), Vec<MvnReportError>> {
+    let errors: Vec<MvnReportError> = program
+        .lines
+        .iter()
+        .filter_map(|line| {
+            let validator = LineValidator::new(&line.line, &line.address, label_map);
+            validator.validate().err()
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
     }
-    Ok(())
 }
 
 struct LineValidator<'a, 'b> {
@@ -43,8 +56,10 @@ impl<'b> LineValidator<'_, 'b> {
             Instruction::Relational(_) => match &self.line.operation.operand.value {
                 Operand::Numeric(_) => Err(MvnReportError::new(
                     self.line.operation.operand.position,
-                    Some("numeric operand cannot be imported nor exported".to_string()),
-                )),
+                    Some(Message::NumericOperandOnImportExport.to_string()),
+                )
+                .with_code(ErrorCode::NumericOperandOnImportExport)
+                .with_span(self.line.operation.operand.span)),
                 _ => Ok(()),
             },
             _ => Ok(()),
@@ -58,11 +73,10 @@ impl<'b> LineValidator<'_, 'b> {
                 _ => match &self.line.operation.operand.value {
                     Operand::Symbolic(_) => Err(MvnReportError::new(
                         self.line.operation.operand.position,
-                        Some(
-                            "symbolic operand cannot be used to reserve addresses or set positions"
-                                .to_string(),
-                        ),
-                    )),
+                        Some(Message::SymbolicOperandOnPositional.to_string()),
+                    )
+                    .with_code(ErrorCode::SymbolicOperandOnPositional)
+                    .with_span(self.line.operation.operand.span)),
                     _ => Ok(()),
                 },
             },
@@ -73,10 +87,22 @@ impl<'b> LineValidator<'_, 'b> {
     fn undefined_label(&self) -> ValidatorResult<'b> {
         match &self.line.operation.operand.value {
             Operand::Symbolic(label) => match &self.label_map.get(label) {
-                None => Err(MvnReportError::new(
-                    self.line.operation.operand.position,
-                    Some("undefined label used as operand".to_string()),
-                )),
+                None => {
+                    let error = MvnReportError::new(
+                        self.line.operation.operand.position,
+                        Some(Message::UndefinedLabel.to_string()),
+                    )
+                    .with_code(ErrorCode::UndefinedLabel)
+                    .with_span(self.line.operation.operand.span);
+                    // Imported labels are part of the label map as well
+                    let labels = self.label_map.keys().map(Label::name);
+                    Err(match suggestion::closest(label.name(), labels) {
+                        Some(suggestion) => {
+                            error.with_help(Message::DidYouMean(suggestion).to_string())
+                        }
+                        None => error,
+                    })
+                }
                 Some(_) => Ok(()),
             },
             _ => Ok(()),
@@ -87,8 +113,10 @@ impl<'b> LineValidator<'_, 'b> {
         if self.address.position > 0xFFF {
             Err(MvnReportError::new(
                 self.line.position(),
-                Some("address outside memory".to_string()),
-            ))
+                Some(Message::AddressOutsideMemory.to_string()),
+            )
+            .with_code(ErrorCode::AddressOutsideMemory)
+            .with_span(self.line.span()))
         } else {
             Ok(())
         }
@@ -109,10 +137,10 @@ impl<'b> LineValidator<'_, 'b> {
                     if immediate > 0xFFFF {
                         Err(MvnReportError::new(
                             self.line.operation.operand.position,
-                            Some(
-                                "immediate over 16 bits for constant pseudoinstruction".to_string(),
-                            ),
-                        ))
+                            Some(Message::ConstantTooWide.to_string()),
+                        )
+                        .with_code(ErrorCode::ConstantTooWide)
+                        .with_span(self.line.operation.operand.span))
                     } else {
                         Ok(())
                     }
@@ -121,8 +149,10 @@ impl<'b> LineValidator<'_, 'b> {
                     if immediate > 0xFFF {
                         Err(MvnReportError::new(
                             self.line.operation.operand.position,
-                            Some("immediate cannot be larger than 12 bits".to_string()),
-                        ))
+                            Some(Message::ImmediateTooWide.to_string()),
+                        )
+                        .with_code(ErrorCode::ImmediateTooWide)
+                        .with_span(self.line.operation.operand.span))
                     } else {
                         Ok(())
                     }
@@ -189,7 +219,7 @@ mod tests {
     }
 
     impl TestProgram {
-        fn validate(self) -> ValidatorResult<'static> {
+        fn validate(self) -> Result<(), Vec<MvnReportError>> {
             let (program, label_map) = self.render();
             validate(&program, &label_map)
         }
@@ -357,6 +387,26 @@ mod tests {
         assert!(test_program.validate().is_err());
     }
 
+    #[test]
+    fn misspelled_label_should_suggest_defined_label() {
+        let test_program = TestProgram {
+            load_label: "ONF".into(),
+            ..Default::default()
+        };
+        let errors = test_program.validate().unwrap_err();
+        assert_eq!(errors[0].help, vec!["did you mean `ONE`?".to_owned()]);
+    }
+
+    #[test]
+    fn misspelled_label_should_suggest_imported_label() {
+        let test_program = TestProgram {
+            load_label: "IMPORTS".into(),
+            ..Default::default()
+        };
+        let errors = test_program.validate().unwrap_err();
+        assert_eq!(errors[0].help, vec!["did you mean `IMPORT`?".to_owned()]);
+    }
+
     // #[test]
     // fn code_inside_address_space_should_pass() {
 
@@ -395,4 +445,15 @@ mod tests {
         };
         assert!(test_program.validate().is_err());
     }
+
+    #[test]
+    fn every_invalid_line_should_be_reported() {
+        let test_program = TestProgram {
+            import: 0.into(),
+            load_label: "FOO".into(),
+            load_value: 0x1000,
+            ..Default::default()
+        };
+        assert_eq!(test_program.validate().unwrap_err().len(), 3);
+    }
 }
@@ -1,10 +1,7 @@
-// use std::fmt;
-use annotate_snippets::{
-    display_list::{DisplayList, FormatOptions},
-    snippet::{Annotation, AnnotationType, Slice, Snippet, SourceAnnotation},
-};
-
+use machine_code::types::DebugRecord;
 use utils::error::MvnReportError;
+use utils::io::Source;
+use utils::report::{exit_with_errors, MessageFormat};
 
 use crate::processor::address::{Address, AddressedLine, AddressedProgram, LabelMap};
 use crate::types::{Instruction, Line, Operand};
@@ -15,92 +12,95 @@ use crate::types::{Instruction, Line, Operand};
 //     // error: impl std::io::Write
 // ) {}
 pub fn print(
-    program: &str,
-    validator_output: Result<(AddressedProgram, LabelMap), MvnReportError>,
+    source: Source,
+    validator_output: Result<(AddressedProgram, LabelMap), Vec<MvnReportError>>,
+    debug: bool,
+    message_format: MessageFormat,
 ) {
     match validator_output {
-        Ok((program, label_map)) => print_program(program, label_map),
-        Err(error) => print_error(program, error),
+        Ok((program, label_map)) => {
+            print!(
+                "{}",
+                render(program, label_map, debug.then_some(source.name))
+            )
+        }
+        Err(errors) => exit_with_errors(&errors, &[source], message_format),
     }
 }
 
-fn print_error(program: &str, error: MvnReportError) {
-    let line: usize = error.position.line.try_into().unwrap();
-    let source = program.lines().nth(line - 1).unwrap();
-    let column = error.position.column.min(source.len() - 1);
+/// Intermediate code of the program, one word per line. With a file name,
+/// each word is followed by the place it was assembled from, and labels
+/// on lines placing no word get a line of their own
+pub fn render(program: AddressedProgram, label_map: LabelMap, debug: Option<&str>) -> String {
+    let mut code = String::new();
 
-    let message = error.message.unwrap_or_default();
+    for AddressedLine { address, line } in program.lines.iter() {
+        let Some((operation_address, operation_value)) = encode(address, line, &label_map) else {
+            let label = line.label.as_ref().map(|label| label.value);
+            let label_address = label.and_then(|label| label_map.get(&label));
+            if let (Some(file), Some(label_address)) = (debug, label_address) {
+                let position = line.position();
+                let record =
+                    DebugRecord::new(file, position.line, position.column).with_label(label);
+                let nibble = resolve_nibble(label_address, &Address::default()) as u32;
+                code.push_str(&format!(
+                    "{:04X} ; {record}\n",
+                    (nibble << 12) + label_address.position
+                ));
+            }
+            continue;
+        };
+        let operation = &line.operation;
 
-    let snippet = Snippet {
-        title: Some(Annotation {
-            label: Some("error while handling input file"),
-            id: None,
-            annotation_type: AnnotationType::Error,
-        }),
-        footer: vec![],
-        slices: vec![Slice {
-            source,
-            line_start: line,
-            origin: None,
-            fold: false,
-            annotations: vec![SourceAnnotation {
-                label: &message,
-                annotation_type: AnnotationType::Error,
-                range: (column, column + 1), // TODO Use proper span length
-            }],
-        }],
-        opt: FormatOptions {
-            color: true,
-            ..Default::default()
-        },
-    };
-    let dl = DisplayList::from(snippet);
-    eprintln!("{}", dl);
+        code.push_str(&format!(
+            "{:04X} {:04X}",
+            operation_address, operation_value
+        ));
+        if let Instruction::Relational(relational_mneumonic) = &operation.instruction.value {
+            if let Operand::Symbolic(relational_label) = &operation.operand.value {
+                code.push_str(&format!(" ; {} {}", relational_mneumonic, relational_label));
+            }
+        } else if let Some(file) = debug {
+            let position = line.position();
+            let label = line.label.as_ref().map(|label| label.value);
+            let record = DebugRecord::new(file, position.line, position.column).with_label(label);
+            code.push_str(&format!(" ; {record}"));
+        }
+        code.push('\n');
+    }
+    code
 }
 
-// fn write_program(program: AddressedProgram, label_map: LabelMap, output: impl fmt::Write) {
-fn print_program(program: AddressedProgram, label_map: LabelMap) {
+/// Address, with its properties in the top nibble, and word of a line. Positional
+/// lines place no word, and neither do lines referring to unknown labels
+pub fn encode(address: &Address, line: &Line, label_map: &LabelMap) -> Option<(u32, u32)> {
     let default_address = Address::default();
+    let operation = &line.operation;
 
-    for AddressedLine { address, line } in program.lines.iter() {
-        let Line {
-            label: _,
-            operation,
-        } = line;
-
-        let instruction_value: u8 = match operation.instruction.value {
-            Instruction::Positional(_) => continue,
-            Instruction::Normal(mneumonic) => mneumonic.into(),
-            _ => 0,
-        };
+    let instruction_value: u8 = match operation.instruction.value {
+        Instruction::Positional(_) => return None,
+        Instruction::Normal(mneumonic) => mneumonic.into(),
+        _ => 0,
+    };
 
-        let (operand_address, operand_value) = match &operation.operand.value {
-            Operand::Symbolic(label) => {
-                let operand_address = label_map.get(label).unwrap();
-                (operand_address, operand_address.position)
-            }
-            Operand::Numeric(immediate) => (&default_address, *immediate),
-        };
+    let (operand_address, operand_value) = match &operation.operand.value {
+        Operand::Symbolic(label) => {
+            let operand_address = label_map.get(label)?;
+            (operand_address, operand_address.position)
+        }
+        Operand::Numeric(immediate) => (&default_address, *immediate),
+    };
 
-        let operation_value = ((instruction_value as u32) << 12) + operand_value;
+    let operation_value = ((instruction_value as u32) << 12) + operand_value;
 
-        let nibble_value = resolve_nibble(address, operand_address);
+    let nibble_value = resolve_nibble(address, operand_address);
 
-        let operation_address = ((nibble_value as u32) << 12) + address.position;
+    let operation_address = ((nibble_value as u32) << 12) + address.position;
 
-        print!("{:04X} {:04X}", operation_address, operation_value);
-        if let Instruction::Relational(relational_mneumonic) = &operation.instruction.value {
-            if let Operand::Symbolic(relational_label) = &operation.operand.value {
-                print!(" ; {} {}", relational_mneumonic, relational_label.0);
-            }
-        }
-        println!();
-    }
+    Some((operation_address, operation_value))
 }
 
 fn resolve_nibble(line: &Address, operand: &Address) -> u8 {
-    (0u8  << 3) // One bit is not necessary, so it's fixed at zero
-    + ((line.relocatable as u8) << 2)
-    + ((operand.relocatable as u8) << 1)
-    + (operand.imported as u8)
+    // One bit is not necessary, so it's fixed at zero
+    ((line.relocatable as u8) << 2) + ((operand.relocatable as u8) << 1) + (operand.imported as u8)
 }
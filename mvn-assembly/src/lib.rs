@@ -1,2 +1,4 @@
+#[cfg(feature = "serde")]
+pub mod owned;
 pub mod parser;
 pub mod types;
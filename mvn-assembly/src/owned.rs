@@ -0,0 +1,190 @@
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use utils::types::{Position, Token};
+
+use crate::types::{Instruction, Label, Line, Operand, Operation, Program};
+
+/*
+ * Owned counterpart of `Program`, which borrows from the source it was
+ * parsed from, so that it may be stored, sent across threads or written
+ * as JSON. Spans are left out, so tokens only keep where they start
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssemblyProgram {
+    pub lines: Vec<AssemblyLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssemblyLine {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<AssemblyToken<AssemblyLabel>>,
+    pub instruction: AssemblyToken<Instruction>,
+    pub operand: AssemblyToken<AssemblyOperand>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AssemblyLabel(pub String);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssemblyOperand {
+    Symbolic(AssemblyLabel),
+    Numeric(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssemblyToken<T> {
+    pub value: T,
+    pub line: u32,
+    pub column: usize,
+}
+
+impl<T> AssemblyToken<T> {
+    fn new<U>(token: &Token<U>, value: T) -> Self {
+        Self {
+            value,
+            line: token.position.line,
+            column: token.position.column,
+        }
+    }
+
+    fn to_token<'a, U>(&'a self, value: impl FnOnce(&'a T) -> U) -> Token<U> {
+        Token::new(Position::new(self.line, self.column), value(&self.value))
+    }
+}
+
+impl AssemblyProgram {
+    pub fn new(program: &Program) -> Self {
+        Self {
+            lines: program.lines.iter().map(AssemblyLine::new).collect(),
+        }
+    }
+
+    /// Program borrowing its labels from this one, as if parsed again
+    pub fn to_program(&self) -> Program<'_> {
+        Program::new(self.lines.iter().map(AssemblyLine::to_line).collect())
+    }
+}
+
+impl AssemblyLine {
+    pub fn new(line: &Line) -> Self {
+        let operation = &line.operation;
+        let operand = match &operation.operand.value {
+            Operand::Symbolic(label) => AssemblyOperand::Symbolic(AssemblyLabel::new(label)),
+            Operand::Numeric(value) => AssemblyOperand::Numeric(*value),
+        };
+        Self {
+            label: line
+                .label
+                .as_ref()
+                .map(|label| AssemblyToken::new(label, AssemblyLabel::new(&label.value))),
+            instruction: AssemblyToken::new(&operation.instruction, operation.instruction.value),
+            operand: AssemblyToken::new(&operation.operand, operand),
+        }
+    }
+
+    pub fn to_line(&self) -> Line<'_> {
+        Line::new(
+            self.label
+                .as_ref()
+                .map(|label| label.to_token(AssemblyLabel::to_label)),
+            Operation::new(
+                self.instruction.to_token(|instruction| *instruction),
+                self.operand.to_token(|operand| match operand {
+                    AssemblyOperand::Symbolic(label) => Operand::Symbolic(label.to_label()),
+                    AssemblyOperand::Numeric(value) => Operand::Numeric(*value),
+                }),
+            ),
+        )
+    }
+}
+
+impl AssemblyLabel {
+    pub fn new(label: &Label) -> Self {
+        Self(label.name().to_owned())
+    }
+
+    pub fn to_label(&self) -> Label<'_> {
+        Label::new(&self.0)
+    }
+}
+
+// Instructions are written as their mneumonics, as in the source
+impl Serialize for Instruction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Instruction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mneumonic = String::deserialize(deserializer)?;
+        Instruction::ALL
+            .into_iter()
+            .find(|instruction| instruction.to_string() == mneumonic)
+            .ok_or_else(|| D::Error::custom(format!("unknown mneumonic `{mneumonic}`")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parser::Parse;
+
+    const PROGRAM: &str = indoc! {"
+                > MAIN
+                < PRINT
+        MAIN    LV  /2
+                SC  PRINT
+        BUFFER  $   /1
+                # MAIN
+    "};
+
+    #[test]
+    fn should_keep_program() {
+        let (_, program) = Program::parse_assembler(PROGRAM.into()).unwrap();
+        let owned = AssemblyProgram::new(&program);
+        assert_eq!(owned.to_program(), program);
+    }
+
+    #[test]
+    fn should_round_trip_through_json() {
+        let (_, program) = Program::parse_assembler(PROGRAM.into()).unwrap();
+        let owned = AssemblyProgram::new(&program);
+        let json = serde_json::to_string(&owned).unwrap();
+        assert_eq!(
+            serde_json::from_str::<AssemblyProgram>(&json).unwrap(),
+            owned
+        );
+    }
+
+    #[test]
+    fn should_write_json() {
+        let (_, program) = Program::parse_assembler("LOOP JP LOOP".into()).unwrap();
+        let json = serde_json::to_value(AssemblyProgram::new(&program)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "lines": [{
+                    "label": {"value": "LOOP", "line": 1, "column": 1},
+                    "instruction": {"value": "JP", "line": 1, "column": 6},
+                    "operand": {"value": {"symbolic": "LOOP"}, "line": 1, "column": 9}
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_mneumonic_should_fail() {
+        let json = r#"{"lines": [{
+            "instruction": {"value": "XX", "line": 1, "column": 1},
+            "operand": {"value": {"numeric": 0}, "line": 1, "column": 4}
+        }]}"#;
+        let error = serde_json::from_str::<AssemblyProgram>(json).unwrap_err();
+        assert!(error.to_string().starts_with("unknown mneumonic `XX`"));
+    }
+}
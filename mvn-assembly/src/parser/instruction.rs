@@ -1,25 +1,55 @@
-use nom::{branch::alt, combinator::map};
-use utils::error_or;
+use nom::character::complete::satisfy;
+use nom::combinator::{map, not};
+use nom::{branch::alt, sequence::terminated};
+use utils::code::ErrorCode;
+use utils::i18n::Message;
+use utils::suggestion;
 
-use super::error::{LocatedIResult, Span};
+use super::error::{LocatedIResult, MvnParseError, Span};
 use super::Parse;
 use crate::types::{mneumonic, Instruction};
 
 impl<'a> Parse<'a> for Instruction {
     fn parse_assembler(input: Span<'a>) -> LocatedIResult<'a, Self> {
-        let mneumonic = alt((
-            map(mneumonic::NormalMneumonic::parse_assembler, |o| {
-                Self::Normal(o)
-            }),
-            map(mneumonic::PositionalMneumonic::parse_assembler, |o| {
-                Self::Positional(o)
-            }),
-            map(mneumonic::RelationalMneumonic::parse_assembler, |o| {
-                Self::Relational(o)
-            }),
-        ))(input);
-
-        error_or!(mneumonic, input, "invalid mneumonic")
+        // Mneumonics must be whole words, so that `JZZ` isn't read as `JZ`
+        let mneumonic = terminated(
+            alt((
+                map(mneumonic::NormalMneumonic::parse_assembler, |o| {
+                    Self::Normal(o)
+                }),
+                map(mneumonic::PositionalMneumonic::parse_assembler, |o| {
+                    Self::Positional(o)
+                }),
+                map(mneumonic::RelationalMneumonic::parse_assembler, |o| {
+                    Self::Relational(o)
+                }),
+            )),
+            not(satisfy(|c| c.is_alphanumeric() || c == '_')),
+        )(input);
+
+        match mneumonic {
+            Err(nom::Err::Error(_)) => Err(nom::Err::Error(unknown_mneumonic(input))),
+            result => result,
+        }
+    }
+}
+
+fn unknown_mneumonic(input: Span<'_>) -> MvnParseError<'_> {
+    let word = input
+        .fragment()
+        .split(char::is_whitespace)
+        .next()
+        .unwrap_or_default();
+    if word.is_empty() {
+        return MvnParseError::new(Message::ExpectedMneumonic.to_string(), input)
+            .with_code(ErrorCode::UnknownMneumonic);
+    }
+    let error = MvnParseError::new(Message::UnknownMneumonic(word).to_string(), input)
+        .with_code(ErrorCode::UnknownMneumonic);
+    let mneumonics = Instruction::ALL.map(|instruction| instruction.to_string());
+    match suggestion::closest(word, mneumonics.iter().map(String::as_str)) {
+        Some(mneumonic) => error.with_help(Message::DidYouMean(mneumonic).to_string()),
+        None => error,
     }
 }
 
@@ -47,4 +77,22 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn should_not_parse_mneumonic_prefix() {
+        assert!(Instruction::parse_assembler(Span::new("JZZ /0")).is_err());
+    }
+
+    #[test]
+    fn should_suggest_similar_mneumonic() {
+        let error = match Instruction::parse_assembler(Span::new("JZZ /0")) {
+            Err(nom::Err::Error(error)) => error,
+            _ => panic!("mneumonic should not be parsed"),
+        };
+        assert_eq!(error.message.unwrap(), "unknown mnemonic `JZZ`");
+        assert_eq!(
+            error.help.unwrap(),
+            format!("did you mean `{}`?", NormalMneumonic::JumpIfZero)
+        );
+    }
 }
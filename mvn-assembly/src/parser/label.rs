@@ -1,7 +1,9 @@
 use nom::character::complete::space1;
-use nom::combinator::{map, not};
+use nom::combinator::{map, not, recognize};
 use nom::sequence::terminated;
+use utils::code::ErrorCode;
 use utils::error_or;
+use utils::i18n::Message;
 
 use super::error::{LocatedIResult, Span};
 use super::identifier;
@@ -11,12 +13,8 @@ use crate::types::{Instruction, Label};
 impl<'a> Parse<'a> for Label<'a> {
     fn parse_assembler(input: Span<'a>) -> LocatedIResult<'a, Self> {
         let label = not(terminated(Instruction::parse_assembler, space1))(input)
-            .and_then(|(input, _)| map(identifier, Self::new)(input));
-        error_or!(
-            label,
-            input,
-            "invalid label; perhaps you used non-ASCII characters or started with a number"
-        )
+            .and_then(|(input, _)| map(recognize(identifier), Self)(input));
+        error_or!(label, input, ErrorCode::InvalidLabel, Message::InvalidLabel)
     }
 }
 
@@ -57,8 +55,12 @@ mod tests {
         ]
     }
 
-    fn relational_mneumonics() -> [RelationalMneumonic; 2] {
-        [RelationalMneumonic::Export, RelationalMneumonic::Import]
+    fn relational_mneumonics() -> [RelationalMneumonic; 3] {
+        [
+            RelationalMneumonic::Export,
+            RelationalMneumonic::WeakExport,
+            RelationalMneumonic::Import,
+        ]
     }
 
     #[test]
@@ -73,7 +75,7 @@ mod tests {
 
     #[test]
     fn should_parse_label_starting_with_mneumonic() {
-        let label = format!("{}FOO", NormalMneumonic::Jump.to_string());
+        let label = format!("{}FOO", NormalMneumonic::Jump);
         assert!(Label::parse_assembler(label.as_str().into()).is_ok());
     }
 
@@ -81,7 +83,7 @@ mod tests {
     fn should_not_parse_normal_mneumonic() {
         for mneumonic in normal_mneumonics().into_iter() {
             let mut mneumonic = mneumonic.to_string();
-            mneumonic.push_str(" ");
+            mneumonic.push(' ');
             assert!(Label::parse_assembler(mneumonic.as_str().into()).is_err());
         }
     }
@@ -90,7 +92,7 @@ mod tests {
     fn should_not_parse_positional_mneumonic() {
         for mneumonic in positional_mneumonics().into_iter() {
             let mut mneumonic = mneumonic.to_string();
-            mneumonic.push_str(" ");
+            mneumonic.push(' ');
             assert!(Label::parse_assembler(mneumonic.as_str().into()).is_err());
         }
     }
@@ -99,7 +101,7 @@ mod tests {
     fn should_not_parse_relational_mneumonic() {
         for mneumonic in relational_mneumonics().into_iter() {
             let mut mneumonic = mneumonic.to_string();
-            mneumonic.push_str(" ");
+            mneumonic.push(' ');
             assert!(Label::parse_assembler(mneumonic.as_str().into()).is_err());
         }
     }
@@ -3,9 +3,9 @@ use nom::combinator::opt;
 use nom::sequence::{delimited, terminated, tuple};
 use utils::{comment_or_space, types::Token};
 
-use super::error::{LocatedIResult, Span};
+use super::error::{LocatedIResult, MvnParseError, Span};
 use super::Parse;
-use crate::types::{Label, Line, Operation};
+use crate::types::{Instruction, Label, Line, Operation};
 
 impl<'a> Parse<'a> for Line<'a> {
     fn parse_assembler(input: Span<'a>) -> LocatedIResult<'a, Self> {
@@ -19,18 +19,38 @@ impl<'a> Parse<'a> for Line<'a> {
         )(input)
         .map(|(remainder, (label, operation))| (remainder, Self::new(label, operation)))
         .map_err(|e| match e {
-            nom::Err::Error(e) | nom::Err::Failure(e) => nom::Err::Failure(e),
+            nom::Err::Error(e) | nom::Err::Failure(e) => {
+                nom::Err::Failure(misspelled_mneumonic(input).unwrap_or(e))
+            }
             nom::Err::Incomplete(e) => nom::Err::Incomplete(e),
         })
     }
 }
 
+/*
+ * A misspelled mneumonic at the start of a line is read as a label,
+ * which moves the error to the operand. When the first word resembles
+ * a mneumonic and the second doesn't, the first word is reported instead
+ */
+fn misspelled_mneumonic(input: Span<'_>) -> Option<MvnParseError<'_>> {
+    let (input, _) = space0::<_, MvnParseError>(input).ok()?;
+    let (rest, _) = terminated(Label::parse_assembler, space1)(input).ok()?;
+    match Instruction::parse_assembler(rest) {
+        Err(nom::Err::Error(error)) if error.help.is_none() => (),
+        _ => return None,
+    }
+    match Instruction::parse_assembler(input) {
+        Err(nom::Err::Error(error)) if error.help.is_some() => Some(error),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::{mneumonic::*, *};
     use pretty_assertions::assert_eq;
-    use utils::types::Position;
+    use utils::types::{Position, SourceSpan};
 
     #[test]
     fn should_parse_without_label() {
@@ -107,4 +127,28 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn should_report_misspelled_mneumonic_taken_for_label() {
+        for (input, column) in [("JZZ /0", 1), ("   JZZ /0", 4), ("LOOP JZZ /0", 6)] {
+            let error = match Line::parse_assembler(input.into()) {
+                Err(nom::Err::Failure(error)) => error,
+                _ => panic!("line should not be parsed"),
+            };
+            assert_eq!(error.span.get_column(), column);
+            assert_eq!(error.message.unwrap(), "unknown mnemonic `JZZ`");
+            assert!(error.help.is_some());
+        }
+    }
+
+    #[test]
+    fn should_locate_tokens() {
+        let (_, line) = Line::parse_assembler("   LOOP JP /10 ; Foo".into()).unwrap();
+        let label = line.label.as_ref().unwrap();
+        assert_eq!(label.span, SourceSpan::new(3, 7));
+        assert_eq!(label.value.span(), SourceSpan::new(3, 7));
+        assert_eq!(line.operation.instruction.span, SourceSpan::new(8, 10));
+        assert_eq!(line.operation.operand.span, SourceSpan::new(11, 14));
+        assert_eq!(line.span(), SourceSpan::new(3, 14));
+    }
 }
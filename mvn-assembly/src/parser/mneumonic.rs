@@ -1,14 +1,16 @@
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::combinator::value;
+use utils::code::ErrorCode;
 use utils::error_or;
+use utils::i18n::Message;
 
 use super::error::{LocatedIResult, Span};
 use super::Parse;
 use crate::types::mneumonic::*;
 
 impl<'a> Parse<'a> for NormalMneumonic {
-    fn parse_assembler(input: Span<'a>) -> LocatedIResult<Self> {
+    fn parse_assembler(input: Span<'a>) -> LocatedIResult<'a, Self> {
         let mneumonic = alt((
             value(Self::Jump, tag(Self::Jump.to_string().as_str())),
             value(Self::JumpIfZero, tag(Self::JumpIfZero.to_string().as_str())),
@@ -47,7 +49,8 @@ impl<'a> Parse<'a> for NormalMneumonic {
         error_or!(
             mneumonic,
             input,
-            "mneumonic does not match that of any known instruction"
+            ErrorCode::UnknownMneumonic,
+            Message::UnknownInstruction
         )
     }
 }
@@ -73,14 +76,17 @@ impl<'a> Parse<'a> for PositionalMneumonic {
         error_or!(
             mneumonic,
             input,
-            "mneumonic does not match that of any known pseudo-instruction"
+            ErrorCode::UnknownMneumonic,
+            Message::UnknownPseudoInstruction
         )
     }
 }
 
 impl<'a> Parse<'a> for RelationalMneumonic {
     fn parse_assembler(input: Span<'a>) -> LocatedIResult<'a, Self> {
+        // Weak exports come first, since they start with the export mneumonic
         let mneumonic = alt((
+            value(Self::WeakExport, tag(Self::WeakExport.to_string().as_str())),
             value(Self::Export, tag(Self::Export.to_string().as_str())),
             value(Self::Import, tag(Self::Import.to_string().as_str())),
         ))(input);
@@ -88,7 +94,8 @@ impl<'a> Parse<'a> for RelationalMneumonic {
         error_or!(
             mneumonic,
             input,
-            "mneumonic does not match that of any known pseudo-instruction"
+            ErrorCode::UnknownMneumonic,
+            Message::UnknownPseudoInstruction
         )
     }
 }
@@ -189,6 +196,12 @@ mod tests {
                 .1,
             RelationalMneumonic::Export
         );
+        assert_eq!(
+            RelationalMneumonic::parse_assembler(Span::new(">?"))
+                .unwrap()
+                .1,
+            RelationalMneumonic::WeakExport
+        );
         assert_eq!(
             RelationalMneumonic::parse_assembler(Span::new("<"))
                 .unwrap()
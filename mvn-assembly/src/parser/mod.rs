@@ -8,7 +8,7 @@ pub mod program;
 
 use nom_locate::position;
 pub use utils::error;
-use utils::types::Token;
+use utils::types::{SourceSpan, Token};
 
 pub trait Parse<'a>: Sized {
     fn parse_assembler(input: error::Span<'a>) -> error::LocatedIResult<'a, Self>;
@@ -19,7 +19,7 @@ impl<'a, T: Parse<'a>> Parse<'a> for Token<T> {
         let (input, position) = position(input)?;
         let position = position.into();
         let (rest, value) = T::parse_assembler(input)?;
-        let token = Token { position, value };
+        let token = Token::new(position, value).with_span(SourceSpan::between(input, rest));
         Ok((rest, token))
     }
 }
@@ -37,7 +37,7 @@ use nom::sequence::pair;
  *
  * From: https://github.com/Geal/nom/blob/main/doc/nom_recipes.md#identifiers
  */
-pub fn identifier(input: error::Span) -> error::LocatedIResult<&str> {
+pub fn identifier(input: error::Span<'_>) -> error::LocatedIResult<'_, &str> {
     let (remainder, matched) = recognize(pair(
         alt((alpha1, tag("_"))),
         many0_count(alt((alphanumeric1, tag("_")))),
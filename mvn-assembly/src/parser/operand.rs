@@ -3,7 +3,9 @@ use nom::bytes::complete::tag;
 use nom::character::complete;
 use nom::combinator::map;
 use nom::sequence::preceded;
+use utils::code::ErrorCode;
 use utils::error_or;
+use utils::i18n::Message;
 use utils::{ascii, hexadecimal};
 
 use super::error::{LocatedIResult, Span};
@@ -23,13 +25,19 @@ impl<'a> Parse<'a> for Operand<'a> {
             )),
             Self::new_numeric,
         )(input);
-        let numeric_operand =
-            error_or!(numeric_operand, input, "could not parse numeric immediate");
+        let numeric_operand = error_or!(
+            numeric_operand,
+            input,
+            ErrorCode::InvalidImmediate,
+            Message::InvalidImmediate
+        );
 
         let symbolic_operand = map(Label::parse_assembler, Self::new_symbolic)(input);
         // `types::Label::parse` already returns a custom error
 
-        if numeric_operand.is_err() {
+        // Operands starting with a numeric prefix can't be labels
+        let numeric_prefix = input.starts_with(['/', '=', '"']);
+        if numeric_operand.is_err() && !numeric_prefix {
             symbolic_operand
         } else {
             numeric_operand
@@ -96,4 +104,14 @@ mod tests {
         );
         assert!(Operand::parse_assembler(Span::new("1label")).is_err());
     }
+
+    #[test]
+    fn should_report_invalid_immediate() {
+        for input in ["/G0", "=A", "\""] {
+            match Operand::parse_assembler(Span::new(input)) {
+                Err(nom::Err::Error(error)) => assert_eq!(error.code, ErrorCode::InvalidImmediate),
+                _ => panic!("operand should not be parsed"),
+            }
+        }
+    }
 }
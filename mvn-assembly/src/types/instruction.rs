@@ -9,6 +9,35 @@ pub enum Instruction {
     Relational(RelationalMneumonic),
 }
 
+impl Instruction {
+    pub const ALL: [Self; 24] = [
+        Self::Normal(NormalMneumonic::Jump),
+        Self::Normal(NormalMneumonic::JumpIfZero),
+        Self::Normal(NormalMneumonic::JumpIfNegative),
+        Self::Normal(NormalMneumonic::LoadValue),
+        Self::Normal(NormalMneumonic::Add),
+        Self::Normal(NormalMneumonic::Subtract),
+        Self::Normal(NormalMneumonic::Multiply),
+        Self::Normal(NormalMneumonic::Divide),
+        Self::Normal(NormalMneumonic::Load),
+        Self::Normal(NormalMneumonic::Memory),
+        Self::Normal(NormalMneumonic::Subroutine),
+        Self::Normal(NormalMneumonic::ReturnFromSubrotine),
+        Self::Normal(NormalMneumonic::HaltMachine),
+        Self::Normal(NormalMneumonic::GetData),
+        Self::Normal(NormalMneumonic::PutData),
+        Self::Normal(NormalMneumonic::OperatingSystem),
+        Self::Normal(NormalMneumonic::SetConstant),
+        Self::Positional(PositionalMneumonic::SetAbsoluteOrigin),
+        Self::Positional(PositionalMneumonic::SetRelocatableOrigin),
+        Self::Positional(PositionalMneumonic::ReserveMemory),
+        Self::Positional(PositionalMneumonic::SetEnd),
+        Self::Relational(RelationalMneumonic::Export),
+        Self::Relational(RelationalMneumonic::WeakExport),
+        Self::Relational(RelationalMneumonic::Import),
+    ];
+}
+
 impl fmt::Display for Instruction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mneumonic = match &self {
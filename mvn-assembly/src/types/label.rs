@@ -1,23 +1,68 @@
+use std::cmp::Ordering;
 use std::fmt;
 
-#[derive(Debug, PartialEq, Clone, Eq, PartialOrd, Ord)]
-pub struct Label<'a>(pub &'a str);
+use utils::error::Span;
+use utils::types::{Position, SourceSpan};
+
+/*
+ * Labels keep the span they were parsed from so that they can still
+ * be located after parsing, but are compared only by their names
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct Label<'a>(pub Span<'a>);
 
-// FIXME Store Span instead of str to locate after parsing
 impl<'a> Label<'a> {
     pub fn new(input: &'a str) -> Self {
-        Self(input)
+        Self(Span::new(input))
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.0.fragment()
+    }
+
+    pub fn position(&self) -> Position {
+        self.0.into()
+    }
+
+    pub fn span(&self) -> SourceSpan {
+        self.0.into()
     }
 }
 
 impl<'a> std::convert::From<&'a str> for Label<'a> {
     fn from(value: &'a str) -> Self {
+        Label::new(value)
+    }
+}
+
+impl<'a> std::convert::From<Span<'a>> for Label<'a> {
+    fn from(value: Span<'a>) -> Self {
         Label(value)
     }
 }
 
+impl PartialEq for Label<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl Eq for Label<'_> {}
+
+impl PartialOrd for Label<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Label<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name().cmp(other.name())
+    }
+}
+
 impl<'a> fmt::Display for Label<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.name())
     }
 }
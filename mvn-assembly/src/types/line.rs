@@ -1,6 +1,6 @@
 use std::fmt;
 
-use utils::types::{Position, Token};
+use utils::types::{Position, SourceSpan, Token};
 
 use super::{Label, Operation};
 
@@ -23,6 +23,15 @@ impl<'a> Line<'a> {
         };
         Position::new(self.operation.instruction.position.line, column)
     }
+
+    pub fn span(&self) -> SourceSpan {
+        let start = if let Some(token) = &self.label {
+            token.span.start
+        } else {
+            self.operation.instruction.span.start
+        };
+        SourceSpan::new(start, self.operation.operand.span.end)
+    }
 }
 
 impl<'a> fmt::Display for Line<'a> {
@@ -5,16 +5,18 @@ use dotenv_codegen::dotenv;
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RelationalMneumonic {
     Export,
+    // Exports that are overridden by an export of the same symbol from another program
+    WeakExport,
     Import,
 }
 
 impl fmt::Display for RelationalMneumonic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mneumonic = match &self {
-            Self::Export => dotenv!("MNEUMONIC_EXPORT"),
-            Self::Import => dotenv!("MNEUMONIC_IMPORT"),
-        };
-        write!(f, "{mneumonic}")
+        match &self {
+            Self::Export => write!(f, "{}", dotenv!("MNEUMONIC_EXPORT")),
+            Self::WeakExport => write!(f, "{}?", dotenv!("MNEUMONIC_EXPORT")),
+            Self::Import => write!(f, "{}", dotenv!("MNEUMONIC_IMPORT")),
+        }
     }
 }
 
@@ -29,6 +31,10 @@ mod tests {
             RelationalMneumonic::Export.to_string(),
             dotenv!("MNEUMONIC_EXPORT")
         );
+        assert_eq!(
+            RelationalMneumonic::WeakExport.to_string(),
+            format!("{}?", dotenv!("MNEUMONIC_EXPORT"))
+        );
         assert_eq!(
             RelationalMneumonic::Import.to_string(),
             dotenv!("MNEUMONIC_IMPORT")
@@ -0,0 +1,19 @@
+use linker::processor::parse;
+use relocator::image;
+use relocator::writer::{print_image, ImageFormat};
+use utils::error::MvnReportError;
+use utils::io::Source;
+
+/// Intel HEX files are turned back into programs, anything else is read as an absolute program
+pub fn convert(source: Source, format: ImageFormat, fill: u8) -> Result<(), MvnReportError> {
+    if source.content.trim_start().starts_with(':') {
+        let program = image::from_intel_hex(source.content)?;
+        let program = parse(Source::new(source.name, &program))?;
+        print_image(&program, format, fill);
+    } else {
+        let program = parse(source)?;
+        image::check_absolute(&program)?;
+        print_image(&program, format, fill);
+    }
+    Ok(())
+}
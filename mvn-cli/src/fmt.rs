@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use assembler::parser::Parse;
+use assembler::types::mneumonic::NormalMneumonic;
+use assembler::types::{Instruction, Line, Operand, Program};
+use utils::error::MvnReportError;
+use utils::io::Source;
+
+/// Columns at which the mnemonic, operand and trailing comment of each line start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub label_width: usize,
+    pub mneumonic_width: usize,
+    pub operand_width: usize,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            label_width: 8,
+            mneumonic_width: 4,
+            operand_width: 8,
+        }
+    }
+}
+
+/*
+ * Sources are parsed in uppercase, as by the assembler, but comments are
+ * taken from the original text so that their case is kept. Comments and
+ * blank lines are discarded by the parser, so every line of the source
+ * is visited and the parsed ones are looked up by their line number
+ */
+pub fn format(source: Source, layout: &Layout) -> Result<String, MvnReportError> {
+    let uppercase = source.content.to_ascii_uppercase();
+    let (_, program) =
+        Program::parse_assembler(uppercase.as_str().into()).map_err(|e| match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => MvnReportError::from(e),
+            nom::Err::Incomplete(e) => panic!("Unhandled error `{e:?}` occured"),
+        })?;
+    let lines: HashMap<u32, &Line> = program
+        .lines
+        .iter()
+        .map(|line| (line.position().line, line))
+        .collect();
+
+    let mut formatted = Vec::new();
+    let mut offset = 0;
+    for (index, text) in source.content.split_inclusive('\n').enumerate() {
+        let line = match lines.get(&(index as u32 + 1)) {
+            Some(line) => format_line(
+                line,
+                &uppercase,
+                &source.content[..offset + text.len()],
+                layout,
+            ),
+            None => format_comment(text.trim_end(), layout),
+        };
+        formatted.push(line);
+        offset += text.len();
+    }
+    // Blank lines at the end are dropped, and the last line ends with a newline
+    while formatted.last().is_some_and(String::is_empty) {
+        formatted.pop();
+    }
+    Ok(formatted.into_iter().map(|line| line + "\n").collect())
+}
+
+// `text` runs up to the end of the line, so that the comment is whatever follows the operand
+fn format_line(line: &Line, uppercase: &str, text: &str, layout: &Layout) -> String {
+    let label = line.label.as_ref().map_or("", |label| label.value.name());
+    let instruction = line.operation.instruction.value;
+    let operand = &line.operation.operand;
+    let operand_text = match &operand.value {
+        Operand::Symbolic(label) => label.name().to_owned(),
+        Operand::Numeric(value) => canonical_literal(
+            &uppercase[operand.span.start..operand.span.end],
+            *value,
+            instruction,
+        ),
+    };
+    let instruction = instruction.to_string();
+    let comment = text[operand.span.end..].trim();
+
+    let mut formatted = pad(label, layout.label_width);
+    formatted.push_str(&pad(&instruction, layout.mneumonic_width));
+    if comment.is_empty() {
+        formatted.push_str(&operand_text);
+    } else {
+        formatted.push_str(&pad(&operand_text, layout.operand_width));
+        formatted.push_str(comment);
+    }
+    formatted.trim_end().to_owned()
+}
+
+// Comments starting a line stay there, while indented ones go to the mnemonic column
+fn format_comment(text: &str, layout: &Layout) -> String {
+    let comment = text.trim_start();
+    if comment.is_empty() || comment.len() == text.len() {
+        return comment.to_owned();
+    }
+    format!("{}{comment}", " ".repeat(layout.label_width))
+}
+
+/*
+ * Hexadecimal literals are written with as many digits as what they
+ * stand for, four for the words of `K` and three for addresses and
+ * other operands, unless they need more. Decimal ones lose their
+ * leading zeros, and ASCII ones are left as they are
+ */
+fn canonical_literal(literal: &str, value: u32, instruction: Instruction) -> String {
+    let width = match instruction {
+        Instruction::Normal(NormalMneumonic::SetConstant) => 4,
+        _ => 3,
+    };
+    match literal.chars().next() {
+        Some('=') => format!("={value}"),
+        Some('/') => format!("/{value:0width$X}"),
+        _ => literal.to_owned(),
+    }
+}
+
+// Columns too narrow for their text are still separated by a space
+fn pad(text: &str, width: usize) -> String {
+    if text.len() < width {
+        format!("{text:<width$}")
+    } else {
+        format!("{text} ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn format_source(content: &str, layout: &Layout) -> String {
+        format(Source::new("main.asm", content), layout).unwrap()
+    }
+
+    #[test]
+    fn should_align_columns() {
+        let source = indoc! {"
+            ; Soma dois números
+              < print
+            main lv =007 ; Carrega sete
+            \t  sc print
+              ; Termina
+
+            hm\tmain
+            um   k /0
+            mm /0a
+            dois k \"a;
+               # main
+
+
+        "};
+        assert_eq!(
+            format_source(source, &Layout::default()),
+            indoc! {"
+                ; Soma dois números
+                        <   PRINT
+                MAIN    LV  =7      ; Carrega sete
+                        SC  PRINT
+                        ; Termina
+
+                        HM  MAIN
+                UM      K   /0000
+                        MM  /00A
+                DOIS    K   \"A;
+                        #   MAIN
+            "}
+        );
+    }
+
+    #[test]
+    fn should_use_given_widths() {
+        let layout = Layout {
+            label_width: 4,
+            mneumonic_width: 3,
+            operand_width: 6,
+        };
+        let source = "INICIO LV /1 ;um\n  JP INICIO\n";
+        assert_eq!(
+            format_source(source, &layout),
+            "INICIO LV /001  ;um\n    JP INICIO\n"
+        );
+    }
+
+    #[test]
+    fn formatted_source_should_be_kept() {
+        let source = indoc! {"
+            ; Programa
+                    >   MAIN
+            MAIN    LV  /003    ; Três
+                    HM  MAIN
+                    #   MAIN
+        "};
+        assert_eq!(format_source(source, &Layout::default()), source);
+    }
+
+    #[test]
+    fn invalid_source_should_fail() {
+        let error = format(Source::new("main.asm", "        LV\n"), &Layout::default());
+        assert!(error.is_err());
+    }
+}
@@ -1,7 +1,20 @@
-use std::path::PathBuf;
+mod convert;
+mod fmt;
+mod objdump;
+
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use clap::{Parser, Subcommand};
-use utils::io::{file_exists, read_to_string};
+use linker::archive::{Archive, Member};
+use linker::types::uppercase_program;
+use relocator::writer::ImageFormat;
+use utils::code::ErrorCode;
+use utils::error::MvnReportError;
+use utils::i18n::{set_lang, Lang, Message};
+use utils::io::{file_exists, read_verbatim, Source};
+use utils::report::{exit_with_errors, print_errors, MessageFormat};
+use utils::types::Position;
 use utils::Executor;
 
 #[derive(Parser)]
@@ -9,39 +22,273 @@ use utils::Executor;
 struct Cli {
     #[arg(short, long)]
     output: Option<PathBuf>,
+    // Read before parsing by `Lang::detect`, so that help is translated as well
+    #[arg(long, global = true, value_enum, help = Message::HelpLang.to_string())]
+    lang: Option<Lang>,
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    Assemble {
-        #[arg(short, long, value_parser = file_exists)]
+    Assemble(assembler::Args),
+    Link(linker::Args),
+    Relocate(relocator::Args),
+    #[command(about = Message::HelpAr.to_string())]
+    Ar {
+        #[command(subcommand)]
+        command: ArCommands,
+    },
+    #[command(about = Message::HelpObjdump.to_string())]
+    Objdump {
+        #[arg(short, long, value_parser = file_exists, help = Message::HelpInput.to_string())]
         input: PathBuf,
+        #[arg(long, value_enum, default_value_t, help = Message::HelpMessageFormat.to_string())]
+        message_format: MessageFormat,
     },
-    Link(linker::Args),
-    Relocate {
-        #[arg(short, long, value_parser = file_exists)]
+    #[command(about = Message::HelpConvert.to_string())]
+    Convert {
+        #[arg(short, long, value_parser = file_exists, help = Message::HelpInput.to_string())]
         input: PathBuf,
-        #[arg(short, long, value_parser = clap_num::maybe_hex::<u16>)]
-        base: u16,
+        #[arg(long, value_enum, default_value_t, help = Message::HelpFormat.to_string())]
+        format: ImageFormat,
+        #[arg(
+            long,
+            default_value_t,
+            value_parser = clap_num::maybe_hex::<u8>,
+            help = Message::HelpFill.to_string()
+        )]
+        fill: u8,
+        #[arg(long, value_enum, default_value_t, help = Message::HelpMessageFormat.to_string())]
+        message_format: MessageFormat,
+    },
+    #[command(about = Message::HelpFmt.to_string())]
+    Fmt {
+        #[arg(required = true, value_parser = file_exists, help = Message::HelpSources.to_string())]
+        sources: Vec<PathBuf>,
+        #[arg(long, help = Message::HelpCheck.to_string())]
+        check: bool,
+        #[arg(long, default_value_t = 8, help = Message::HelpLabelWidth.to_string())]
+        label_width: usize,
+        #[arg(long, default_value_t = 4, help = Message::HelpMneumonicWidth.to_string())]
+        mnemonic_width: usize,
+        #[arg(long, default_value_t = 8, help = Message::HelpOperandWidth.to_string())]
+        operand_width: usize,
+        #[arg(long, value_enum, default_value_t, help = Message::HelpMessageFormat.to_string())]
+        message_format: MessageFormat,
+    },
+    #[command(about = Message::HelpExplain.to_string())]
+    Explain {
+        #[arg(help = Message::HelpCode.to_string())]
+        code: ErrorCode,
     },
 }
 
+#[derive(Subcommand)]
+enum ArCommands {
+    #[command(about = Message::HelpArCreate.to_string())]
+    Create {
+        #[arg(help = Message::HelpArchive.to_string())]
+        archive: PathBuf,
+        #[arg(required = true, value_parser = file_exists, help = Message::HelpMembers.to_string())]
+        members: Vec<PathBuf>,
+        #[arg(long, value_enum, default_value_t, help = Message::HelpMessageFormat.to_string())]
+        message_format: MessageFormat,
+    },
+    #[command(about = Message::HelpArList.to_string())]
+    List {
+        #[arg(value_parser = file_exists, help = Message::HelpArchive.to_string())]
+        archive: PathBuf,
+        #[arg(long, value_enum, default_value_t, help = Message::HelpMessageFormat.to_string())]
+        message_format: MessageFormat,
+    },
+    #[command(about = Message::HelpArExtract.to_string())]
+    Extract {
+        #[arg(value_parser = file_exists, help = Message::HelpArchive.to_string())]
+        archive: PathBuf,
+        #[arg(help = Message::HelpMembers.to_string())]
+        members: Vec<String>,
+        #[arg(long, value_enum, default_value_t, help = Message::HelpMessageFormat.to_string())]
+        message_format: MessageFormat,
+    },
+}
+
+fn ar(command: &ArCommands) {
+    match command {
+        ArCommands::Create {
+            archive,
+            members,
+            message_format,
+        } => {
+            // Members are named after their files, without the directories
+            let members: Vec<Member> = members
+                .iter()
+                .map(|path| {
+                    let name = path.file_name().unwrap_or(path.as_os_str());
+                    Member::new(
+                        name.to_string_lossy().into_owned(),
+                        uppercase_program(&read_verbatim(path)),
+                    )
+                })
+                .collect();
+            match Archive::new(members.clone()) {
+                Ok(output) => fs::write(archive, output.to_string()).expect("failed to write file"),
+                Err(error) => {
+                    let sources: Vec<Source> = members.iter().map(Member::source).collect();
+                    exit_with_errors(&[error], &sources, *message_format);
+                }
+            }
+        }
+        ArCommands::List {
+            archive,
+            message_format,
+        } => {
+            let archive = read_archive(archive, *message_format);
+            for (i, member) in archive.members.iter().enumerate() {
+                println!("{}", member.name);
+                for symbol in archive.exports(i) {
+                    println!("    > {symbol}");
+                }
+            }
+        }
+        ArCommands::Extract {
+            archive,
+            members,
+            message_format,
+        } => {
+            let archive_name = archive.display().to_string();
+            let content = read_verbatim(archive);
+            let archive = parse_archive(&archive_name, &content, *message_format);
+            let members: Vec<&Member> = if members.is_empty() {
+                archive.members.iter().collect()
+            } else {
+                // Missing members are reported at the archive, all at once
+                let missing: Vec<MvnReportError> = members
+                    .iter()
+                    .filter(|name| archive.member(name).is_none())
+                    .map(|name| {
+                        MvnReportError::new(
+                            Position::new(1, 1),
+                            Some(Message::ArchiveMemberNotFound(name).to_string()),
+                        )
+                        .with_code(ErrorCode::ArchiveMemberNotFound)
+                        .with_file(&archive_name)
+                    })
+                    .collect();
+                if !missing.is_empty() {
+                    let source = Source::new(&archive_name, &content);
+                    exit_with_errors(&missing, &[source], *message_format);
+                }
+                members
+                    .iter()
+                    .filter_map(|name| archive.member(name))
+                    .collect()
+            };
+            // Archives naming anything but plain files are rejected when read, but only the
+            // file name is kept all the same
+            for member in members {
+                if let Some(file) = Path::new(&member.name).file_name() {
+                    fs::write(file, &member.content).expect("failed to write file");
+                }
+            }
+        }
+    }
+}
+
+fn read_archive(path: &PathBuf, message_format: MessageFormat) -> Archive {
+    let name = path.display().to_string();
+    let content = read_verbatim(path);
+    parse_archive(&name, &content, message_format)
+}
+
+fn parse_archive(name: &str, content: &str, message_format: MessageFormat) -> Archive {
+    match Archive::parse(content) {
+        Ok(archive) => archive,
+        Err(error) => {
+            let source = Source::new(name, content);
+            exit_with_errors(&[error.with_file(name)], &[source], message_format);
+        }
+    }
+}
+
+// Sources are rewritten only when their formatting changes
+fn format_sources(
+    sources: &[PathBuf],
+    check: bool,
+    layout: &fmt::Layout,
+    message_format: MessageFormat,
+) {
+    let mut failed = false;
+    for path in sources {
+        let name = path.display().to_string();
+        let content = read_verbatim(path);
+        let source = Source::new(&name, &content);
+        match fmt::format(source, layout) {
+            Ok(formatted) if formatted == content => (),
+            Ok(_) if check => {
+                eprintln!("{}", Message::NotFormatted(&name));
+                failed = true;
+            }
+            Ok(formatted) => fs::write(path, formatted).expect("failed to write file"),
+            Err(error) => {
+                print_errors(&[error.with_file(&name)], &[source], message_format);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
 fn main() {
+    set_lang(Lang::detect(std::env::args()));
     let cli = Cli::parse();
     match &cli.command {
-        Commands::Assemble { input } => {
-            let program = read_to_string(input);
-            let process_result = assembler::processor::process(&program);
-            assembler::writer::print(&program, process_result);
-        }
+        Commands::Assemble(args) => args.execute(),
         Commands::Link(args) => args.execute(),
-        Commands::Relocate { input, base } => {
-            let program = read_to_string(input);
-            let process_result = relocator::processor::process(&program, *base);
-            relocator::writer::print(process_result);
+        Commands::Relocate(args) => args.execute(),
+        Commands::Ar { command } => ar(command),
+        Commands::Objdump {
+            input,
+            message_format,
+        } => {
+            let name = input.display().to_string();
+            let program = uppercase_program(&read_verbatim(input));
+            let source = Source::new(&name, &program);
+            match linker::processor::parse(source) {
+                Ok(program) => print!("{}", objdump::dump(program)),
+                Err(error) => exit_with_errors(&[error], &[source], *message_format),
+            }
+        }
+        Commands::Convert {
+            input,
+            format,
+            fill,
+            message_format,
+        } => {
+            let name = input.display().to_string();
+            let program = uppercase_program(&read_verbatim(input));
+            let source = Source::new(&name, &program);
+            if let Err(error) = convert::convert(source, *format, *fill) {
+                exit_with_errors(&[error], &[source], *message_format);
+            }
         }
+        Commands::Fmt {
+            sources,
+            check,
+            label_width,
+            mnemonic_width,
+            operand_width,
+            message_format,
+        } => {
+            let layout = fmt::Layout {
+                label_width: *label_width,
+                mneumonic_width: *mnemonic_width,
+                operand_width: *operand_width,
+            };
+            format_sources(sources, *check, &layout, *message_format);
+        }
+        Commands::Explain { code } => print!("{}", code.explanation()),
     }
 }
-
@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+
+use linker::types::mneumonic::RelationalMneumonic;
+use linker::types::{
+    AddressPosition, AddressedLine, AddressedProgram, DebugRecord, Instruction,
+    MachineAddressProperties, Operand,
+};
+use utils::table;
+
+/*
+ * Spells out what is packed into each line of machine code, which is
+ * otherwise decoded by hand: the mneumonic and operand of each word,
+ * the properties in the first nibble of its address, the symbol table
+ * and which operands the linker or relocator are going to change.
+ * Programs assembled with debug records also get the source of each
+ * word and the address of each label
+ */
+pub fn dump(mut program: AddressedProgram) -> String {
+    let label_records = std::mem::take(&mut program.labels);
+    let (imports, exports, instructions) = program.partition();
+    let imports: BTreeMap<AddressPosition, String> = imports
+        .into_iter()
+        .map(|line| {
+            let (label, position, _) = line.destruct();
+            (position, label.to_string())
+        })
+        .collect();
+
+    let summary = [("absolute", false), ("relocatable", true)]
+        .into_iter()
+        .map(|(kind, relocatable)| {
+            let positions: Vec<AddressPosition> = instructions
+                .iter()
+                .filter(|line| line.address.value.properties.line_relocatable == relocatable)
+                .map(|line| line.address.value.position)
+                .collect();
+            let range = match (positions.iter().min(), positions.iter().max()) {
+                (Some(first), Some(last)) => format!("{first:04X}-{:04X}", last + 0x1),
+                _ => "-".to_owned(),
+            };
+            vec![kind.to_owned(), positions.len().to_string(), range]
+        })
+        .collect();
+
+    let debug = instructions.iter().any(|line| line.debug.is_some()) || !label_records.is_empty();
+    let words = instructions
+        .iter()
+        .map(|line| {
+            let instruction = match line.operation.instruction.value {
+                instruction @ Instruction::Normal(_) => instruction.to_string(),
+                _ => "-".to_owned(),
+            };
+            let mut row = vec![
+                format!("{:04X}", line.address.value.position),
+                format!(
+                    "{:X} {:X}{}",
+                    line.address.value,
+                    line.operation.instruction.value,
+                    line.operation.operand.value
+                ),
+                instruction,
+                operand(line, &imports),
+                properties(line.address.value.properties),
+            ];
+            if debug {
+                row.push(line.debug.as_ref().map_or("-".to_owned(), source));
+            }
+            row
+        })
+        .collect();
+    let mut words_header = vec!["ADDRESS", "CODE", "MNEMONIC", "OPERAND", "PROPERTIES"];
+    if debug {
+        words_header.push("SOURCE");
+    }
+
+    // Labels on lines placing no word, such as those reserving memory, come on their own
+    let mut labels: Vec<(AddressPosition, &DebugRecord)> = instructions
+        .iter()
+        .filter_map(|line| Some((line.address.value.position, line.debug.as_ref()?)))
+        .chain(
+            label_records
+                .iter()
+                .map(|record| (record.address.value.position, &record.debug)),
+        )
+        .collect();
+    labels.sort_by_key(|(position, _)| *position);
+    let labels = labels
+        .into_iter()
+        .filter_map(|(position, record)| {
+            Some(vec![
+                record.label?.to_string(),
+                format!("{position:04X}"),
+                source(record),
+            ])
+        })
+        .collect();
+
+    let exports = exports
+        .into_iter()
+        .map(|line| {
+            let weak = line
+                .relational_annotation
+                .as_ref()
+                .is_some_and(|annotation| {
+                    annotation.operation.instruction.value
+                        == Instruction::Relational(RelationalMneumonic::WeakExport)
+                });
+            let (label, position, relocatable) = line.destruct();
+            let kind = match (relocatable, weak) {
+                (true, false) => "relocatable",
+                (false, false) => "absolute",
+                (true, true) => "relocatable weak",
+                (false, true) => "absolute weak",
+            };
+            vec![
+                label.to_string(),
+                format!("{position:04X}"),
+                kind.to_owned(),
+            ]
+        })
+        .collect();
+
+    let import_rows = imports
+        .iter()
+        .map(|(position, symbol)| vec![format!("{position:03X}"), symbol.clone()])
+        .collect();
+
+    let relocations = instructions
+        .iter()
+        .filter_map(|line| {
+            let properties = line.address.value.properties;
+            let kind = match (properties.operand_imported, properties.operand_relocatable) {
+                (true, _) => "imported operand",
+                (false, true) => "relocatable operand",
+                (false, false) => return None,
+            };
+            Some(vec![
+                format!("{:04X}", line.address.value.position),
+                kind.to_owned(),
+                operand(line, &imports),
+            ])
+        })
+        .collect();
+
+    let mut tables = vec![
+        table::render("Summary", &["KIND", "WORDS", "RANGE"], summary),
+        table::render("Words", &words_header, words),
+        table::render("Exports", &["SYMBOL", "ADDRESS", "KIND"], exports),
+        table::render("Imports", &["INDEX", "SYMBOL"], import_rows),
+        table::render("Relocations", &["ADDRESS", "KIND", "TARGET"], relocations),
+    ];
+    if debug {
+        tables.push(table::render(
+            "Labels",
+            &["LABEL", "ADDRESS", "SOURCE"],
+            labels,
+        ));
+    }
+    tables.join("\n")
+}
+
+fn source(record: &DebugRecord) -> String {
+    format!("{}:{}:{}", record.file, record.line, record.column)
+}
+
+// Imported operands refer to the symbol imported at their position in the symbol table
+fn operand(line: &AddressedLine, imports: &BTreeMap<AddressPosition, String>) -> String {
+    match &line.operation.operand.value {
+        Operand::Numeric(operand) if line.address.value.properties.operand_imported => {
+            match imports.get(operand) {
+                Some(symbol) => format!("{operand:03X} ({symbol})"),
+                None => format!("{operand:03X} (?)"),
+            }
+        }
+        Operand::Numeric(operand) => format!("{operand:03X}"),
+        Operand::Symbolic(label) => label.to_string(),
+    }
+}
+
+fn properties(properties: MachineAddressProperties) -> String {
+    let properties: Vec<&str> = [
+        (properties.line_relocatable, "line relocatable"),
+        (properties.operand_relocatable, "operand relocatable"),
+        (properties.operand_imported, "operand imported"),
+    ]
+    .into_iter()
+    .filter_map(|(set, property)| set.then_some(property))
+    .collect();
+    match properties.is_empty() {
+        true => "-".to_owned(),
+        false => properties.join(", "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use linker::processor::parse;
+    use pretty_assertions::assert_eq;
+    use utils::io::Source;
+
+    #[test]
+    fn should_dump_program() {
+        let program = indoc! {"
+            1000 0000 ; < ADD_TWO
+            6000 0002 ; > TWICE
+            0000 3003 ; MAIN    LV  /3
+            1002 A000 ;         SC  ADD_TWO
+            4002 0000 ; TWICE   $   /1
+            6004 4000 ;         AD  MAIN
+        "};
+        let program = parse(Source::new("main.int", program)).unwrap();
+        assert_eq!(
+            dump(program),
+            indoc! {"
+                Summary
+                  KIND         WORDS  RANGE
+                  absolute     2      0000-0003
+                  relocatable  2      0002-0005
+
+                Words
+                  ADDRESS  CODE       MNEMONIC  OPERAND        PROPERTIES
+                  0000     0000 3003  LV        003            -
+                  0002     1002 A000  SC        000 (ADD_TWO)  operand imported
+                  0002     4002 0000  JP        000            line relocatable
+                  0004     6004 4000  AD        000            line relocatable, operand relocatable
+
+                Exports
+                  SYMBOL  ADDRESS  KIND
+                  TWICE   0002     relocatable
+
+                Imports
+                  INDEX  SYMBOL
+                  000    ADD_TWO
+
+                Relocations
+                  ADDRESS  KIND                 TARGET
+                  0002     imported operand     000 (ADD_TWO)
+                  0004     relocatable operand  000
+            "}
+        );
+    }
+
+    #[test]
+    fn should_dump_debug_records() {
+        let program = indoc! {"
+            4000 8006 ; ! MAIN.ASM:2:1 MAIN
+            4002 C000 ; ! MAIN.ASM:3:9
+            4004 ; ! MAIN.ASM:4:1 BUFFER
+        "};
+        let program = parse(Source::new("main.int", program)).unwrap();
+        let dump = dump(program);
+        assert!(dump
+            .contains("  0002     4002 C000  HM        000      line relocatable  MAIN.ASM:3:9\n"));
+        assert!(dump.ends_with(indoc! {"
+            Labels
+              LABEL   ADDRESS  SOURCE
+              MAIN    0000     MAIN.ASM:2:1
+              BUFFER  0004     MAIN.ASM:4:1
+        "}));
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Component, Path};
+
+use utils::code::ErrorCode;
+use utils::error::MvnReportError;
+use utils::i18n::Message;
+use utils::io::Source;
+use utils::types::{Position, SourceSpan};
+
+use crate::processor::parse;
+use crate::types::mneumonic::RelationalMneumonic;
+
+const HEADER: &str = "!<mvn-archive>";
+
+/// Program in intermediate code stored in an archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub name: String,
+    pub content: String,
+}
+
+impl Member {
+    pub fn new(name: String, content: String) -> Self {
+        Self { name, content }
+    }
+
+    pub fn source(&self) -> Source<'_> {
+        Source::new(&self.name, &self.content)
+    }
+}
+
+/*
+ * Programs in intermediate code bundled along with an index of the
+ * symbols each of them exports, so that the linker can pick the
+ * members it needs without parsing all of them. Archives are stored
+ * as text, with each member preceded by its number of lines:
+ *
+ *     !<mvn-archive>
+ *     index <number of symbols>
+ *     <symbol> <member number>
+ *     member <number of lines> <name>
+ *     <lines of the member>
+ */
+#[derive(Debug, PartialEq)]
+pub struct Archive {
+    pub index: BTreeMap<String, usize>,
+    pub members: Vec<Member>,
+}
+
+impl Archive {
+    pub fn new(members: Vec<Member>) -> Result<Self, MvnReportError> {
+        let mut index = BTreeMap::new();
+        // Weak exports are indexed unless some member exports the same symbol
+        let mut weak_exports = BTreeMap::new();
+        for (i, member) in members.iter().enumerate() {
+            let program = parse(member.source())?;
+            for label in program.symbols(RelationalMneumonic::WeakExport) {
+                weak_exports.entry(label.name().to_owned()).or_insert(i);
+            }
+            for label in program.symbols(RelationalMneumonic::Export) {
+                if index.insert(label.name().to_owned(), i).is_some() {
+                    return Err(MvnReportError::new(
+                        label.position(),
+                        Some(Message::DuplicateExport(label.name()).to_string()),
+                    )
+                    .with_code(ErrorCode::DuplicateExport)
+                    .with_span(label.span())
+                    .with_file(&member.name));
+                }
+            }
+        }
+        for (symbol, member) in weak_exports {
+            index.entry(symbol).or_insert(member);
+        }
+        Ok(Self { index, members })
+    }
+
+    pub fn parse(content: &str) -> Result<Self, MvnReportError> {
+        let lines: Vec<&str> = content.lines().collect();
+        // Missing lines are reported at the last line there is
+        let line = |i: usize| lines.get(i).copied().ok_or_else(|| malformed(i.max(1)));
+        if line(0)? != HEADER {
+            return Err(malformed(1));
+        }
+
+        let symbol_count: usize = arguments(line(1)?, "index")
+            .and_then(|count| count.parse().ok())
+            .ok_or_else(|| malformed(2))?;
+        let mut index = BTreeMap::new();
+        for i in 2..2 + symbol_count {
+            let (symbol, member) = line(i)?.split_once(' ').ok_or_else(|| malformed(i + 1))?;
+            let member = member.parse().map_err(|_| malformed(i + 1))?;
+            index.insert(symbol.to_owned(), member);
+        }
+
+        let mut members = Vec::new();
+        let mut i = 2 + symbol_count;
+        while i < lines.len() {
+            let (line_count, name) = arguments(lines[i], "member")
+                .and_then(|arguments| arguments.split_once(' '))
+                .ok_or_else(|| malformed(i + 1))?;
+            let line_count: usize = line_count.parse().map_err(|_| malformed(i + 1))?;
+            if !is_plain_file_name(name) {
+                let start = name.as_ptr() as usize - content.as_ptr() as usize;
+                let column = name.as_ptr() as usize - lines[i].as_ptr() as usize + 1;
+                return Err(MvnReportError::new(
+                    Position::new(i as u32 + 1, column),
+                    Some(Message::UnsafeMemberName(name).to_string()),
+                )
+                .with_code(ErrorCode::MalformedArchive)
+                .with_span(SourceSpan::new(start, start + name.len())));
+            }
+            let end = i + 1 + line_count;
+            if end > lines.len() {
+                return Err(malformed(i + 1));
+            }
+            let content = lines[i + 1..end]
+                .iter()
+                .map(|line| format!("{line}\n"))
+                .collect();
+            members.push(Member::new(name.to_owned(), content));
+            i = end;
+        }
+
+        match index.values().any(|member| *member >= members.len()) {
+            true => Err(malformed(2)),
+            false => Ok(Self { index, members }),
+        }
+    }
+
+    pub fn member(&self, name: &str) -> Option<&Member> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    /// Symbols exported by the member at `position`, in alphabetical order
+    pub fn exports(&self, position: usize) -> impl Iterator<Item = &str> {
+        self.index
+            .iter()
+            .filter(move |(_, member)| **member == position)
+            .map(|(symbol, _)| symbol.as_str())
+    }
+}
+
+// Members are extracted to the current directory, so their names can't lead out of it
+fn is_plain_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) && !name.contains(['/', '\\'])
+}
+
+// Entries are introduced by a keyword followed by their arguments
+fn arguments<'a>(entry: &'a str, keyword: &str) -> Option<&'a str> {
+    entry.strip_prefix(keyword)?.strip_prefix(' ')
+}
+
+fn malformed(line: usize) -> MvnReportError {
+    MvnReportError::new(
+        Position::new(line as u32, 1),
+        Some(Message::MalformedArchive.to_string()),
+    )
+    .with_code(ErrorCode::MalformedArchive)
+}
+
+impl fmt::Display for Archive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        writeln!(f, "index {}", self.index.len())?;
+        for (symbol, member) in self.index.iter() {
+            writeln!(f, "{symbol} {member}")?;
+        }
+        for member in self.members.iter() {
+            writeln!(
+                f,
+                "member {} {}",
+                member.content.lines().count(),
+                member.name
+            )?;
+            for line in member.content.lines() {
+                writeln!(f, "{line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn test_members() -> Vec<Member> {
+        vec![
+            Member::new(
+                "subroutine.int".to_owned(),
+                indoc! {"
+                    6100 0102 ; > ADD_TWO
+                    5000 0000 ; < RESULT
+                    4100 0002 ; TWO         K   /2
+                    4102 0000 ; ADD_TWO     $   /1
+                    6104 4100 ;             AD  TWO
+                    5106 9000 ;             MM  RESULT
+                    6108 B102 ;             RS  ADD_TWO
+                "}
+                .to_owned(),
+            ),
+            Member::new(
+                "result.int".to_owned(),
+                indoc! {"
+                    4000 0000 ; > RESULT
+                    4000 0000 ; RESULT  K   /0
+                "}
+                .to_owned(),
+            ),
+        ]
+    }
+
+    const TEST_ARCHIVE: &str = indoc! {"
+        !<mvn-archive>
+        index 2
+        ADD_TWO 0
+        RESULT 1
+        member 7 subroutine.int
+        6100 0102 ; > ADD_TWO
+        5000 0000 ; < RESULT
+        4100 0002 ; TWO         K   /2
+        4102 0000 ; ADD_TWO     $   /1
+        6104 4100 ;             AD  TWO
+        5106 9000 ;             MM  RESULT
+        6108 B102 ;             RS  ADD_TWO
+        member 2 result.int
+        4000 0000 ; > RESULT
+        4000 0000 ; RESULT  K   /0
+    "};
+
+    #[test]
+    fn should_index_exports() {
+        let archive = Archive::new(test_members()).unwrap();
+        assert_eq!(archive.exports(0).collect::<Vec<_>>(), vec!["ADD_TWO"]);
+        assert_eq!(archive.exports(1).collect::<Vec<_>>(), vec!["RESULT"]);
+    }
+
+    #[test]
+    fn should_write_archive() {
+        let archive = Archive::new(test_members()).unwrap();
+        assert_eq!(archive.to_string(), TEST_ARCHIVE);
+    }
+
+    #[test]
+    fn should_read_archive() {
+        let archive = Archive::parse(TEST_ARCHIVE).unwrap();
+        assert_eq!(archive, Archive::new(test_members()).unwrap());
+        assert_eq!(archive.member("result.int"), Some(&test_members()[1]));
+    }
+
+    #[test]
+    fn should_reject_malformed_archive() {
+        let truncated = &TEST_ARCHIVE[..TEST_ARCHIVE.len() - 30];
+        let error = Archive::parse(truncated).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::MalformedArchive));
+        assert_eq!(error.position.line, 13);
+
+        let error = Archive::parse("member 0 foo.int").unwrap_err();
+        assert_eq!(error.position.line, 1);
+    }
+
+    #[test]
+    fn member_names_leading_out_of_directory_should_fail() {
+        for name in [
+            "../../x.int",
+            "/etc/passwd",
+            "lib/x.int",
+            "..",
+            "lib\\x.int",
+        ] {
+            let archive = format!("!<mvn-archive>\nindex 0\nmember 1 {name}\n0000 0000\n");
+            let error = Archive::parse(&archive).unwrap_err();
+            assert_eq!(error.code, Some(ErrorCode::MalformedArchive));
+            assert_eq!(error.position, Position::new(3, 10));
+            assert_eq!(&archive[error.span.start..error.span.end], name);
+        }
+    }
+
+    #[test]
+    fn duplicate_export_in_archive_should_fail() {
+        let mut members = test_members();
+        members.push(Member::new(
+            "copy.int".to_owned(),
+            members[1].content.clone(),
+        ));
+        let error = Archive::new(members).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::DuplicateExport));
+        assert_eq!(error.file.as_deref(), Some("copy.int"));
+    }
+}
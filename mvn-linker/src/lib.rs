@@ -1,18 +1,33 @@
+pub mod archive;
+pub mod map;
 pub mod processor;
+pub mod script;
+pub mod visibility;
 pub mod writer;
 
 pub use machine_code::*;
 
+use std::fs;
 use std::path::PathBuf;
 
+use assembly::parser::Parse;
 use clap::{ArgAction, ArgGroup, Parser};
-use utils::io::{file_exists, read_to_string};
+use nom::combinator::all_consuming;
+use utils::i18n::{Lang, Message};
+use utils::io::{file_exists, read_verbatim, Source};
+use utils::report::{exit_with_errors, MessageFormat};
 use utils::Executor;
 
-use crate::{processor::process, writer::print};
+use crate::archive::Archive;
+use crate::object::{ObjectProgram, ObjectText};
+use crate::processor::process;
+use crate::script::LinkerScript;
+use crate::types::{uppercase_program, Label};
+use crate::visibility::Visibility;
+use crate::writer::{print, Dialect};
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about = Message::HelpLink.to_string(), long_about = None)]
 #[command(group(
     ArgGroup::new("linkage-type")
     .required(true)
@@ -24,20 +39,200 @@ pub struct Args {
         long = "input",
         required = true,
         action = ArgAction::Append,
-        value_parser = file_exists
+        value_parser = file_exists,
+        help = Message::HelpInputs.to_string()
     )]
     pub inputs: Vec<PathBuf>,
-    #[arg(long)]
+    #[arg(
+        short,
+        long = "library",
+        action = ArgAction::Append,
+        value_parser = file_exists,
+        help = Message::HelpLibraries.to_string()
+    )]
+    pub libraries: Vec<PathBuf>,
+    #[arg(short = 'T', long, value_parser = file_exists, help = Message::HelpScript.to_string())]
+    pub script: Option<PathBuf>,
+    #[arg(long, value_name = "FILE", help = Message::HelpMap.to_string())]
+    pub map: Option<PathBuf>,
+    #[arg(long, help = Message::HelpGc.to_string())]
+    pub gc: bool,
+    #[arg(
+        long = "export",
+        value_name = "SYMBOL",
+        value_parser = parse_symbol,
+        help = Message::HelpExport.to_string()
+    )]
+    pub exports: Vec<String>,
+    #[arg(
+        long = "hide",
+        value_name = "SYMBOL",
+        value_parser = parse_symbol,
+        help = Message::HelpHide.to_string()
+    )]
+    pub hidden: Vec<String>,
+    #[arg(
+        long = "rename",
+        value_name = "OLD=NEW",
+        value_parser = parse_rename,
+        help = Message::HelpRename.to_string()
+    )]
+    pub renames: Vec<(String, String)>,
+    #[arg(long, help = Message::HelpPartial.to_string())]
     pub partial: bool,
-    #[arg(long)]
+    #[arg(long, help = Message::HelpComplete.to_string())]
     pub complete: bool,
+    #[arg(long, value_enum, default_value_t, help = Message::HelpDialect.to_string())]
+    pub format: Dialect,
+    #[arg(long, value_enum, default_value_t, help = Message::HelpMessageFormat.to_string())]
+    pub message_format: MessageFormat,
+    // Read before parsing by `Lang::detect`, so that help is translated as well
+    #[arg(long, value_enum, help = Message::HelpLang.to_string())]
+    pub lang: Option<Lang>,
 }
 
 impl Executor for Args {
     fn execute(&self) {
-        let programs: Vec<String> = self.inputs.iter().map(read_to_string).collect();
-        let programs: Vec<&str> = programs.iter().map(String::as_str).collect();
-        let process_result = process(programs, self.complete);
-        print(process_result, self.complete);
+        let names: Vec<String> = self
+            .inputs
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        let inputs = self.read_programs(&names);
+        let archives = self.read_archives();
+        let script_name = self.script.as_ref().map(|path| path.display().to_string());
+        let script_content = self.script.as_ref().map(read_verbatim);
+        let script_source = script_name
+            .as_deref()
+            .zip(script_content.as_deref())
+            .map(|(name, content)| Source::new(name, content));
+        let script = match script_source.map(LinkerScript::parse).transpose() {
+            Ok(script) => script,
+            Err(error) => {
+                let sources: Vec<Source> = script_source.into_iter().collect();
+                exit_with_errors(&[error], &sources, self.message_format);
+            }
+        };
+        let sources: Vec<Source> = std::iter::zip(&names, &inputs)
+            .map(|(name, (content, text))| match text {
+                Some(text) => Source::new(name, &text.text).with_map(&text.map),
+                None => Source::new(name, content),
+            })
+            .collect();
+        let visibility = self.visibility();
+        let process_result = process(
+            &sources,
+            &archives,
+            script.as_ref(),
+            Some(&visibility),
+            self.gc,
+            self.complete,
+        )
+        .map_err(|error| {
+            // JSON objects were rewritten as text, but errors point to them
+            std::iter::zip(&names, &inputs).fold(error, |error, (name, (_, text))| match text {
+                Some(text) => error.locate(&text.map, Some(name)),
+                None => error,
+            })
+        });
+        if let (Some(path), Ok(processor)) = (&self.map, &process_result) {
+            fs::write(path, map::render(processor)).expect("failed to write file");
+        }
+        // Diagnostics point to what was read, before any rewrite
+        let mut sources: Vec<Source> = std::iter::zip(&names, &inputs)
+            .map(|(name, (content, _))| Source::new(name, content))
+            .collect();
+        sources.extend(script_source);
+        sources.extend(
+            archives
+                .iter()
+                .flat_map(|archive| archive.members.iter().map(|member| member.source())),
+        );
+        print(
+            process_result,
+            &sources,
+            self.complete,
+            self.format,
+            self.message_format,
+        );
     }
 }
+
+impl Args {
+    fn visibility(&self) -> Visibility<'_> {
+        Visibility {
+            exports: (!self.exports.is_empty())
+                .then(|| self.exports.iter().map(String::as_str).collect()),
+            hidden: self.hidden.iter().map(String::as_str).collect(),
+            renames: self
+                .renames
+                .iter()
+                .map(|(old, new)| (old.as_str(), new.as_str()))
+                .collect(),
+        }
+    }
+
+    /*
+     * Content of each program, along with its rewrite as text when written
+     * as a JSON object. JSON objects are told apart by their content, so
+     * that they may be mixed with text
+     */
+    fn read_programs(&self, names: &[String]) -> Vec<(String, Option<ObjectText>)> {
+        let mut programs = Vec::new();
+        for (path, name) in std::iter::zip(&self.inputs, names) {
+            let content = read_verbatim(path);
+            if !content.trim_start().starts_with('{') {
+                programs.push((uppercase_program(&content), None));
+                continue;
+            }
+            match ObjectProgram::from_json(&content) {
+                Ok(object) => {
+                    let ObjectText { text, map } = object.to_text(&content);
+                    let text = uppercase_program(&text);
+                    programs.push((content, Some(ObjectText { text, map })));
+                }
+                Err(error) => {
+                    let source = Source::new(name, &content);
+                    exit_with_errors(&[error.with_file(name)], &[source], self.message_format);
+                }
+            }
+        }
+        programs
+    }
+
+    // Members are renamed after their archive so that diagnostics point to it
+    fn read_archives(&self) -> Vec<Archive> {
+        let mut archives = Vec::new();
+        for path in self.libraries.iter() {
+            let name = path.display().to_string();
+            let content = read_verbatim(path);
+            match Archive::parse(&content) {
+                Ok(mut archive) => {
+                    for member in archive.members.iter_mut() {
+                        member.name = format!("{name}({})", member.name);
+                    }
+                    archives.push(archive);
+                }
+                Err(error) => {
+                    let source = Source::new(&name, &content);
+                    exit_with_errors(&[error.with_file(&name)], &[source], self.message_format);
+                }
+            }
+        }
+        archives
+    }
+}
+
+// Programs are read in uppercase, so symbols given as arguments are as well
+fn parse_symbol(symbol: &str) -> Result<String, String> {
+    all_consuming(Label::parse_assembler)(symbol.into())
+        .map(|_| symbol.to_uppercase())
+        .map_err(|_| Message::InvalidLabel.to_string())
+}
+
+fn parse_rename(rename: &str) -> Result<(String, String), String> {
+    rename
+        .split_once('=')
+        .and_then(|(old, new)| parse_symbol(old).ok().zip(parse_symbol(new).ok()))
+        .ok_or_else(|| Message::InvalidRename(rename).to_string())
+}
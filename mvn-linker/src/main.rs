@@ -1,9 +1,11 @@
 use clap::Parser;
+use utils::i18n::{set_lang, Lang};
 use utils::Executor;
 
 use mvn_linker::Args;
 
 fn main() {
+    set_lang(Lang::detect(std::env::args()));
     let args = Args::parse();
     args.execute();
 }
@@ -0,0 +1,213 @@
+use utils::table;
+
+use crate::processor::program::{ModuleLayout, ProgramsProcessor};
+
+/*
+ * Describes where each input ended up after linking, in the spirit
+ * of the map files written by other linkers, so that addresses in
+ * the linked program can be traced back to modules and symbols
+ */
+pub fn render(processor: &ProgramsProcessor) -> String {
+    let modules = processor
+        .modules
+        .iter()
+        .map(|module| {
+            vec![
+                format!("{:04X}", module.base),
+                format!("{:04X}", module.size),
+                module.name.to_owned(),
+            ]
+        })
+        .collect();
+
+    let exports = processor
+        .export_map
+        .iter()
+        .map(|(export, position)| {
+            let kind = match (export.relocatable, export.weak) {
+                (true, false) => "relocatable",
+                (false, false) => "absolute",
+                (true, true) => "relocatable weak",
+                (false, true) => "absolute weak",
+            };
+            let module = exporter(&processor.modules, export.label.name()).unwrap_or_default();
+            vec![
+                format!("{position:04X}"),
+                kind.to_owned(),
+                export.label.to_string(),
+                module.to_owned(),
+            ]
+        })
+        .collect();
+
+    let imports = processor
+        .modules
+        .iter()
+        .flat_map(|module| {
+            module.imports.iter().map(|import| {
+                let resolved_by = exporter(&processor.modules, import.name()).unwrap_or("-");
+                vec![
+                    module.name.to_owned(),
+                    import.to_string(),
+                    resolved_by.to_owned(),
+                ]
+            })
+        })
+        .collect();
+
+    let unresolved = processor
+        .inverted_import_map
+        .keys()
+        .map(|import| {
+            let importers: Vec<&str> = processor
+                .modules
+                .iter()
+                .filter(|module| module.imports.contains(&import.label))
+                .map(|module| module.name)
+                .collect();
+            vec![import.label.to_string(), importers.join(", ")]
+        })
+        .collect();
+
+    // Weak exports given up in favour of the definition that was chosen
+    let overridden = processor
+        .modules
+        .iter()
+        .flat_map(|module| {
+            module.weak_exports.iter().filter_map(|export| {
+                let chosen = exporter(&processor.modules, export.name())?;
+                (chosen != module.name).then(|| {
+                    vec![
+                        export.to_string(),
+                        module.name.to_owned(),
+                        chosen.to_owned(),
+                    ]
+                })
+            })
+        })
+        .collect();
+
+    [
+        table::render("Modules", &["BASE", "SIZE", "MODULE"], modules),
+        table::render("Exports", &["ADDRESS", "KIND", "SYMBOL", "MODULE"], exports),
+        table::render("Imports", &["MODULE", "SYMBOL", "RESOLVED BY"], imports),
+        table::render("Unresolved", &["SYMBOL", "IMPORTED BY"], unresolved),
+        table::render(
+            "Overridden",
+            &["SYMBOL", "MODULE", "OVERRIDDEN BY"],
+            overridden,
+        ),
+    ]
+    .join("\n")
+}
+
+// Module whose definition of `symbol` was chosen, preferring strong exports over weak ones
+fn exporter<'a>(modules: &[ModuleLayout<'a>], symbol: &str) -> Option<&'a str> {
+    let exports = |module: &&ModuleLayout, weak: bool| {
+        module.exports.iter().any(|label| label.name() == symbol)
+            && module
+                .weak_exports
+                .iter()
+                .any(|label| label.name() == symbol)
+                == weak
+    };
+    modules
+        .iter()
+        .find(|module| exports(module, false))
+        .or_else(|| modules.iter().find(|module| exports(module, true)))
+        .map(|module| module.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::processor::process;
+    use utils::io::Source;
+
+    #[test]
+    fn should_render_map() {
+        let main_program = indoc! {"
+            1000 0000 ; < ADD_TWO
+            1000 0001 ; < FOO
+            0000 0002 ; > RESULT
+            0000 0004 ;         JP  MAIN
+            0002 0000 ; RESULT  $   /1
+            0004 3003 ; MAIN    LV  /3
+            1006 A000 ;         SC  ADD_TWO
+            1008 9001 ;         MM  FOO
+            000A C000 ;         HM  /0
+        "};
+        let subroutine_program = indoc! {"
+            6100 0102 ; > ADD_TWO
+            5000 0000 ; < RESULT
+            4100 0002 ; TWO         K   /2
+            4102 0000 ; ADD_TWO     $   /1
+            6104 4100 ;             AD  TWO
+            5106 9000 ;             MM  RESULT
+            6108 B102 ;             RS  ADD_TWO
+        "};
+        let programs = [
+            Source::new("main.int", main_program),
+            Source::new("subroutine.int", subroutine_program),
+        ];
+        let processor = process(&programs, &[], None, None, false, false).unwrap();
+        assert_eq!(
+            render(&processor),
+            indoc! {"
+                Modules
+                  BASE  SIZE  MODULE
+                  0000  0000  main.int
+                  000C  010A  subroutine.int
+
+                Exports
+                  ADDRESS  KIND         SYMBOL   MODULE
+                  010E     relocatable  ADD_TWO  subroutine.int
+                  0002     absolute     RESULT   main.int
+
+                Imports
+                  MODULE          SYMBOL   RESOLVED BY
+                  main.int        ADD_TWO  subroutine.int
+                  main.int        FOO      -
+                  subroutine.int  RESULT   main.int
+
+                Unresolved
+                  SYMBOL  IMPORTED BY
+                  FOO     main.int
+
+                Overridden
+                  SYMBOL  MODULE  OVERRIDDEN BY
+            "}
+        );
+    }
+
+    #[test]
+    fn should_show_chosen_definition_of_weak_symbols() {
+        let default_program = indoc! {"
+            6000 0000 ; >? PRINT
+            4000 C000 ; PRINT   HM  /0
+        "};
+        let main_program = indoc! {"
+            1000 0000 ; < PRINT
+            5000 A000 ;         SC  PRINT
+        "};
+        let print_program = indoc! {"
+            6000 0000 ; > PRINT
+            4000 B000 ; PRINT   RS  PRINT
+        "};
+        let programs = [
+            Source::new("default.int", default_program),
+            Source::new("main.int", main_program),
+            Source::new("print.int", print_program),
+        ];
+        let processor = process(&programs, &[], None, None, false, true).unwrap();
+        let map = render(&processor);
+        assert!(
+            map.contains("0004     relocatable  PRINT   print.int"),
+            "{map}"
+        );
+        assert!(map.contains("PRINT   default.int  print.int"), "{map}");
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use utils::types::Token;
+
+use crate::types::mneumonic::NormalMneumonic;
+use crate::types::{AddressPosition, AddressedLine, AddressedProgram, Instruction, Operand};
+
+use super::program::ProgramsProcessor;
+
+/*
+ * Machine code keeps no trace of where routines and data start, so
+ * relocatable code is split into blocks at every address something
+ * refers to, every export and every module base. Blocks refer to the
+ * blocks their relocatable operands point to and, unless they end
+ * jumping, returning or halting, to the block right after them
+ */
+struct Blocks {
+    // Start of each block along with whether it begins a module
+    starts: BTreeMap<AddressPosition, bool>,
+}
+
+impl Blocks {
+    fn containing(&self, position: AddressPosition) -> Option<AddressPosition> {
+        self.starts
+            .range(..=position)
+            .next_back()
+            .map(|(start, _)| *start)
+    }
+
+    fn after(&self, start: AddressPosition) -> Option<AddressPosition> {
+        self.starts
+            .range(start + 1..)
+            .next()
+            .filter(|(_, begins_module)| !**begins_module)
+            .map(|(start, _)| *start)
+    }
+
+    fn end(&self, start: AddressPosition) -> Option<AddressPosition> {
+        self.starts
+            .range(start + 1..)
+            .next()
+            .map(|(start, _)| *start)
+    }
+}
+
+/// Drops relocatable code and data that can't be reached from the program
+/// entry, absolute code or, when `keep_exports` is set, exported symbols
+pub fn collect_garbage(
+    processor: ProgramsProcessor<'_>,
+    keep_exports: bool,
+) -> ProgramsProcessor<'_> {
+    let lines = &processor.linked_program.lines;
+    let relocatable_lines = || {
+        lines
+            .iter()
+            .filter(|line| line.address.value.properties.line_relocatable)
+    };
+    let relocatable_exports: Vec<AddressPosition> = processor
+        .export_map
+        .iter()
+        .filter(|(export, _)| export.relocatable)
+        .map(|(_, position)| *position)
+        .collect();
+
+    let mut starts = BTreeMap::new();
+    starts.extend(
+        relocatable_exports
+            .iter()
+            .map(|position| (*position, false)),
+    );
+    starts.extend(
+        lines
+            .iter()
+            .filter_map(relocatable_operand)
+            .map(|target| (target, false)),
+    );
+    for module in processor.modules.iter() {
+        let module_lines = relocatable_lines().filter(|line| {
+            let position = line.address.value.position;
+            module.base <= position && position < module.base + module.size
+        });
+        if let Some(first) = module_lines.map(|line| line.address.value.position).min() {
+            starts.insert(first, true);
+        }
+    }
+    let blocks = Blocks { starts };
+
+    let mut references: BTreeMap<AddressPosition, BTreeSet<AddressPosition>> = BTreeMap::new();
+    let mut last_lines: BTreeMap<AddressPosition, &AddressedLine> = BTreeMap::new();
+    for line in relocatable_lines() {
+        let Some(block) = blocks.containing(line.address.value.position) else {
+            continue;
+        };
+        let block_references = references.entry(block).or_default();
+        block_references
+            .extend(relocatable_operand(line).and_then(|target| blocks.containing(target)));
+        let last_line = last_lines.entry(block).or_insert(line);
+        if last_line.address.value.position < line.address.value.position {
+            *last_line = line;
+        }
+    }
+    for (block, line) in last_lines {
+        if !ends_flow(line) {
+            let next = blocks.after(block);
+            references.entry(block).or_default().extend(next);
+        }
+    }
+
+    let entry = processor.modules.first().and_then(|module| {
+        relocatable_lines()
+            .map(|line| line.address.value.position)
+            .filter(|position| module.base <= *position && *position < module.base + module.size)
+            .min()
+    });
+    let mut pending: Vec<AddressPosition> = lines
+        .iter()
+        .filter(|line| !line.address.value.properties.line_relocatable)
+        .filter_map(relocatable_operand)
+        .chain(entry)
+        .chain(relocatable_exports.into_iter().filter(|_| keep_exports))
+        .filter_map(|position| blocks.containing(position))
+        .collect();
+    let mut reachable = BTreeSet::new();
+    while let Some(block) = pending.pop() {
+        if reachable.insert(block) {
+            pending.extend(references.get(&block).into_iter().flatten());
+        }
+    }
+
+    let dropped: Vec<(AddressPosition, AddressPosition)> = blocks
+        .starts
+        .keys()
+        .filter(|start| !reachable.contains(start))
+        .map(|start| {
+            let end = blocks.end(*start).unwrap_or_else(|| {
+                relocatable_lines()
+                    .map(|line| line.address.value.position + 0x2)
+                    .max()
+                    .unwrap_or(*start)
+            });
+            (*start, end)
+        })
+        .collect();
+    repack(processor, &blocks, &dropped)
+}
+
+// Moves what was kept down, over the space taken by `dropped` blocks
+fn repack<'a>(
+    processor: ProgramsProcessor<'a>,
+    blocks: &Blocks,
+    dropped: &[(AddressPosition, AddressPosition)],
+) -> ProgramsProcessor<'a> {
+    let is_dropped = |position: AddressPosition| {
+        blocks
+            .containing(position)
+            .is_some_and(|block| dropped.iter().any(|(start, _)| *start == block))
+    };
+    let moved = |position: AddressPosition| {
+        let offset: AddressPosition = dropped
+            .iter()
+            .filter(|(start, _)| *start < position)
+            .map(|(start, end)| end.min(&position) - start)
+            .sum();
+        position - offset
+    };
+
+    let ProgramsProcessor {
+        mut linked_program,
+        mut export_map,
+        inverted_import_map,
+        mut modules,
+    } = processor;
+    let labels = std::mem::take(&mut linked_program.labels)
+        .into_iter()
+        .filter(|label| {
+            !(label.address.value.properties.line_relocatable
+                && is_dropped(label.address.value.position))
+        })
+        .map(|mut label| {
+            if label.address.value.properties.line_relocatable {
+                label.address.value.position = moved(label.address.value.position);
+            }
+            label
+        })
+        .collect();
+    let lines: AddressedProgram = linked_program
+        .into_iter()
+        .filter(|line| {
+            !(line.address.value.properties.line_relocatable
+                && is_dropped(line.address.value.position))
+        })
+        .map(|mut line| {
+            if line.address.value.properties.line_relocatable {
+                line.address.value.position = moved(line.address.value.position);
+            }
+            if let Some(target) = relocatable_operand(&line) {
+                let operand = &line.operation.operand;
+                line.operation.operand =
+                    Token::new(operand.position, Operand::Numeric(moved(target)))
+                        .with_span(operand.span);
+            }
+            line
+        })
+        .collect();
+
+    export_map.retain(|export, position| !(export.relocatable && is_dropped(*position)));
+    for (export, position) in export_map.iter_mut() {
+        if export.relocatable {
+            *position = moved(*position);
+        }
+    }
+    for module in modules.iter_mut() {
+        let end = moved(module.base + module.size);
+        module.base = moved(module.base);
+        module.size = end - module.base;
+    }
+
+    ProgramsProcessor {
+        linked_program: lines.with_labels(labels),
+        export_map,
+        inverted_import_map,
+        modules,
+    }
+}
+
+fn relocatable_operand(line: &AddressedLine) -> Option<AddressPosition> {
+    match line.operation.operand.value {
+        Operand::Numeric(target) if line.address.value.properties.operand_relocatable => {
+            Some(target)
+        }
+        _ => None,
+    }
+}
+
+fn ends_flow(line: &AddressedLine) -> bool {
+    matches!(
+        line.operation.instruction.value,
+        Instruction::Normal(
+            NormalMneumonic::Jump
+                | NormalMneumonic::ReturnFromSubrotine
+                | NormalMneumonic::HaltMachine
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::processor::process;
+    use crate::visibility::Visibility;
+    use utils::io::Source;
+
+    fn link(programs: &[Source], keep_exports: bool) -> Vec<String> {
+        let processor = process(programs, &[], None, None, false, false).unwrap();
+        collect_garbage(processor, keep_exports)
+            .linked_program
+            .into_iter()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    const MAIN: &str = indoc! {"
+        1000 0000 ; < ADD_TWO
+        4000 3003 ; MAIN    LV  /3
+        5002 A000 ;         SC  ADD_TWO
+        4004 C000 ;         HM  /0
+    "};
+
+    const UTILITIES: &str = indoc! {"
+        6000 0002 ; > ADD_TWO
+        6000 0008 ; > SUB_TWO
+        4000 0002 ; TWO         K   /2
+        4002 0000 ; ADD_TWO     $   /1
+        6004 4000 ;             AD  TWO
+        6006 B002 ;             RS  ADD_TWO
+        4008 0000 ; SUB_TWO     $   /1
+        600A 5000 ;             SB  TWO
+        600C B008 ;             RS  SUB_TWO
+    "};
+
+    #[test]
+    fn should_drop_unreferenced_routines() {
+        let programs = [
+            Source::new("main.int", MAIN),
+            Source::new("utilities.int", UTILITIES),
+        ];
+        assert_eq!(
+            link(&programs, false),
+            vec![
+                "4000 3003",
+                "6002 A008",
+                "4004 C000",
+                "4006 0002",
+                "4008 0000",
+                "600A 4006",
+                "600C B008",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_keep_exported_routines() {
+        let programs = [
+            Source::new("main.int", MAIN),
+            Source::new("utilities.int", UTILITIES),
+        ];
+        assert_eq!(link(&programs, true).len(), 10);
+    }
+
+    #[test]
+    fn should_drop_hidden_exports_on_partial_linkage() {
+        let programs = [
+            Source::new("main.int", MAIN),
+            Source::new("utilities.int", UTILITIES),
+        ];
+        let visibility = Visibility {
+            hidden: vec!["SUB_TWO"],
+            ..Default::default()
+        };
+        let processor = process(&programs, &[], None, Some(&visibility), true, false).unwrap();
+        let exports: Vec<String> = processor
+            .export_map
+            .keys()
+            .map(|export| export.label.to_string())
+            .collect();
+        assert_eq!(exports, vec!["ADD_TWO"]);
+        // `SUB_TWO` is no longer exported, so it is dropped as on complete linkage
+        let lines: Vec<String> = processor
+            .linked_program
+            .into_iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "4000 3003",
+                "6002 A008",
+                "4004 C000",
+                "4006 0002",
+                "4008 0000",
+                "600A 4006",
+                "600C B008",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_keep_code_falling_through() {
+        let program = indoc! {"
+            6000 0004 ; > NEXT
+            6000 0006 ; > UNUSED
+            4000 3003 ; MAIN    LV  /3
+            6002 4008 ;         AD  ONE
+            4004 C000 ; NEXT    HM  /0
+            4006 0001 ; UNUSED  K   /1
+            4008 0001 ; ONE     K   /1
+        "};
+        let programs = [Source::new("main.int", program)];
+        assert_eq!(
+            link(&programs, false),
+            vec!["4000 3003", "6002 4006", "4004 C000", "4006 0001"]
+        );
+    }
+}
@@ -1,28 +1,227 @@
+pub mod gc;
 pub mod program;
 
-use utils::error::MvnReportError;
+use std::collections::{BTreeMap, BTreeSet};
 
+use utils::code::ErrorCode;
+use utils::error::{MvnReportError, MvnReportLabel};
+use utils::i18n::Message;
+use utils::io::Source;
+use utils::types::{Position, SourceSpan};
+
+use crate::archive::Archive;
 use crate::parser::Parse;
-use crate::types::AddressedProgram;
+use crate::script::LinkerScript;
+use crate::types::{
+    mneumonic::RelationalMneumonic, AddressPosition, AddressedProgram, Instruction, Operand,
+};
+use crate::visibility::Visibility;
 
 use program::ProgramsProcessor;
 
-pub fn process(
-    programs: Vec<&str>,
+pub fn process<'a>(
+    programs: &[Source<'a>],
+    archives: &'a [Archive],
+    script: Option<&LinkerScript>,
+    visibility: Option<&Visibility<'a>>,
+    collect_garbage: bool,
     complete_linkage: bool,
-) -> Result<ProgramsProcessor, MvnReportError> {
-    let mut parsed_programs: Vec<AddressedProgram> = Vec::new();
-    for program in programs {
-        let parse_result = AddressedProgram::parse_machine_code(program.into());
-        let (_, program) = parse_result.map_err(|e| match e {
-            nom::Err::Error(e) | nom::Err::Failure(e) => MvnReportError::from(e),
-            nom::Err::Incomplete(e) => panic!("unhandled error `{e:?}` occured"),
-        })?;
-        parsed_programs.push(program);
-    }
-    let processor = ProgramsProcessor::process(parsed_programs)?;
+) -> Result<ProgramsProcessor<'a>, MvnReportError> {
+    let mut parsed_programs: Vec<(&str, AddressedProgram)> = Vec::new();
+    for source in programs {
+        parsed_programs.push((source.name, parse(*source)?));
+    }
+    /*
+     * Archive members are only linked when they export a symbol that
+     * is still unresolved, which may in turn import further symbols,
+     * so members are pulled in one at a time until nothing changes
+     */
+    let mut pulled_members = BTreeSet::new();
+    loop {
+        let next_member = unresolved_symbols(&parsed_programs)
+            .into_iter()
+            .filter_map(|symbol| find_member(archives, symbol))
+            .find(|found| !pulled_members.contains(found));
+        let Some((archive, member)) = next_member else {
+            break;
+        };
+        pulled_members.insert((archive, member));
+        let source = archives[archive].members[member].source();
+        parsed_programs.push((source.name, parse(source)?));
+    }
+    let bases = match script {
+        Some(script) => {
+            let sizes: Vec<(&str, AddressPosition)> = parsed_programs
+                .iter()
+                .map(|(name, program)| (*name, program.relocatable_size()))
+                .collect();
+            Some(script.place(&sizes)?)
+        }
+        None => None,
+    };
+    let uses = symbol_uses(&parsed_programs);
+    let mut processor = ProgramsProcessor::process(parsed_programs, bases)?;
     if complete_linkage && !processor.inverted_import_map.is_empty() {
-        panic!("complete linkage failed due to unresolved imported symbols; after resolution, the symbol table looks like this: {:#?}", processor.inverted_import_map)
+        let unresolved: BTreeSet<&str> = processor
+            .inverted_import_map
+            .keys()
+            .map(|import| import.label.name())
+            .collect();
+        return Err(unresolved_symbols_error(&uses, &unresolved, programs));
+    }
+    // Hidden exports are left out before collecting garbage, so that they don't keep code alive
+    if let Some(visibility) = visibility {
+        processor = visibility.apply(processor)?;
+    }
+    if collect_garbage {
+        // Exports are only visible to later linking steps when linking partially
+        processor = gc::collect_garbage(processor, !complete_linkage);
     }
     Ok(processor)
 }
+
+pub fn parse(source: Source<'_>) -> Result<AddressedProgram<'_>, MvnReportError> {
+    let parse_result = AddressedProgram::parse_machine_code(source.content.into());
+    let (_, program) = parse_result.map_err(|e| match e {
+        nom::Err::Error(e) | nom::Err::Failure(e) => MvnReportError::from(e).with_file(source.name),
+        nom::Err::Incomplete(e) => panic!("unhandled error `{e:?}` occured"),
+    })?;
+    Ok(program)
+}
+
+fn unresolved_symbols<'a>(programs: &[(&str, AddressedProgram<'a>)]) -> BTreeSet<&'a str> {
+    let symbols = |mneumonic| {
+        programs
+            .iter()
+            .flat_map(move |(_, program)| program.symbols(mneumonic))
+            .map(|label| label.name())
+            .collect::<BTreeSet<_>>()
+    };
+    let mut exports = symbols(RelationalMneumonic::Export);
+    exports.extend(symbols(RelationalMneumonic::WeakExport));
+    symbols(RelationalMneumonic::Import)
+        .into_iter()
+        .filter(|symbol| !exports.contains(symbol))
+        .collect()
+}
+
+// Archives are searched in the order they were given, as with inputs
+fn find_member(archives: &[Archive], symbol: &str) -> Option<(usize, usize)> {
+    archives
+        .iter()
+        .enumerate()
+        .find_map(|(i, archive)| archive.index.get(symbol).map(|member| (i, *member)))
+}
+
+// Place where an imported symbol is declared or used as an operand
+struct SymbolUse<'a> {
+    file: &'a str,
+    symbol: &'a str,
+    position: Position,
+    span: SourceSpan,
+    imported: bool,
+}
+
+fn symbol_uses<'a>(programs: &[(&'a str, AddressedProgram<'a>)]) -> Vec<SymbolUse<'a>> {
+    let mut uses = Vec::new();
+    for (file, program) in programs {
+        // Instructions refer to imports by their position in the symbol table
+        let mut imports = BTreeMap::new();
+        for line in program.lines.iter() {
+            let Some(annotation) = &line.relational_annotation else {
+                continue;
+            };
+            let instruction = Instruction::Relational(RelationalMneumonic::Import);
+            if annotation.operation.instruction.value != instruction {
+                continue;
+            }
+            if let (Operand::Numeric(index), Operand::Symbolic(label)) = (
+                &line.operation.operand.value,
+                &annotation.operation.operand.value,
+            ) {
+                imports.insert(*index, label.name());
+                uses.push(SymbolUse {
+                    file,
+                    symbol: label.name(),
+                    position: label.position(),
+                    span: label.span(),
+                    imported: true,
+                });
+            }
+        }
+        for line in program.lines.iter() {
+            let operand = &line.operation.operand;
+            let symbol = match operand.value {
+                Operand::Numeric(index) if line.relational_annotation.is_none() => {
+                    imports.get(&index)
+                }
+                _ => None,
+            };
+            if let (true, Some(symbol)) = (line.address.value.properties.operand_imported, symbol) {
+                uses.push(SymbolUse {
+                    file,
+                    symbol,
+                    position: operand.position,
+                    span: operand.span,
+                    imported: false,
+                });
+            }
+        }
+    }
+    uses
+}
+
+/*
+ * Reported at the first place an unresolved symbol is imported, with
+ * every other import and reference as labels and references listed
+ * in the help, so that they can be found even without the snippets.
+ * The help names lines as they were read, before any rewrite
+ */
+fn unresolved_symbols_error(
+    uses: &[SymbolUse],
+    unresolved: &BTreeSet<&str>,
+    sources: &[Source],
+) -> MvnReportError {
+    let mut uses = uses.iter().filter(|use_| unresolved.contains(use_.symbol));
+    let symbols: Vec<&str> = unresolved.iter().copied().collect();
+    let message = Message::UnresolvedSymbols(&symbols.join(", ")).to_string();
+    let Some(first) = uses.next() else {
+        return MvnReportError::new(Position::new(1, 1), Some(message))
+            .with_code(ErrorCode::UnresolvedSymbols);
+    };
+    let references: Vec<String> = std::iter::once(first)
+        .chain(uses.clone())
+        .filter(|use_| !use_.imported)
+        .map(|use_| {
+            let line = sources
+                .iter()
+                .find(|source| source.name == use_.file)
+                .map_or(use_.position.line, |source| {
+                    source.original_line(use_.position)
+                });
+            let location = format!("{}:{line}", use_.file);
+            Message::UndefinedReferenceAt(use_.symbol, &location).to_string()
+        })
+        .collect();
+    let mut error = MvnReportError::new(first.position, Some(message))
+        .with_code(ErrorCode::UnresolvedSymbols)
+        .with_span(first.span)
+        .with_file(first.file);
+    for use_ in uses {
+        let message = match use_.imported {
+            true => Message::SymbolImportedHere(use_.symbol),
+            false => Message::UndefinedReference(use_.symbol),
+        };
+        let label = MvnReportLabel::new(
+            Some(use_.file.to_owned()),
+            use_.position,
+            message.to_string(),
+        )
+        .with_span(use_.span);
+        error = error.with_label(label);
+    }
+    for reference in references {
+        error = error.with_help(reference);
+    }
+    error
+}
@@ -1,17 +1,22 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use utils::error::MvnReportError;
+use utils::code::ErrorCode;
+use utils::error::{MvnReportError, MvnReportLabel};
+use utils::i18n::Message;
 use utils::types::Token;
 
 use crate::parser::Relocate;
 use crate::types::{
-    AddressPosition, AddressedLine, AddressedProgram, Label, MachineAddress,
-    MachineAddressProperties, Operand, Operation,
+    first_free_base, first_overlap, mneumonic::RelationalMneumonic, AddressPosition, AddressedLine,
+    AddressedProgram, Instruction, Label, LabelRecord, MachineAddress, MachineAddressProperties,
+    Operand, Operation, MEMORY_END,
 };
 
 #[derive(Debug, Eq)]
 pub struct RelocatableLabel<'a> {
     pub relocatable: bool,
+    // Set on exports that give way to an export of the same symbol elsewhere
+    pub weak: bool,
     pub label: Label<'a>,
 }
 
@@ -37,6 +42,7 @@ impl<'a> From<Label<'a>> for RelocatableLabel<'a> {
     fn from(value: Label<'a>) -> Self {
         RelocatableLabel {
             relocatable: false,
+            weak: false,
             label: value,
         }
     }
@@ -44,7 +50,15 @@ impl<'a> From<Label<'a>> for RelocatableLabel<'a> {
 
 impl<'a> RelocatableLabel<'a> {
     pub fn new(relocatable: bool, label: Label<'a>) -> Self {
-        RelocatableLabel { relocatable, label }
+        RelocatableLabel {
+            relocatable,
+            weak: false,
+            label,
+        }
+    }
+
+    pub fn with_weak(self, weak: bool) -> Self {
+        RelocatableLabel { weak, ..self }
     }
 
     pub fn label(&'a self) -> &'a Label<'a> {
@@ -57,64 +71,182 @@ impl<'a> RelocatableLabel<'a> {
 }
 
 type ImportMap<'a> = BTreeMap<AddressPosition, RelocatableLabel<'a>>;
-type ExportMap<'a> = BTreeMap<RelocatableLabel<'a>, AddressPosition>;
+pub type ExportMap<'a> = BTreeMap<RelocatableLabel<'a>, AddressPosition>;
+
+/// Where the relocatable code of a program ended up after linking and
+/// which symbols it relates
+#[derive(Debug, PartialEq)]
+pub struct ModuleLayout<'a> {
+    pub name: &'a str,
+    pub base: AddressPosition,
+    pub size: AddressPosition,
+    pub exports: Vec<Label<'a>>,
+    pub weak_exports: Vec<Label<'a>>,
+    pub imports: Vec<Label<'a>>,
+}
 
 #[derive(Debug)]
 pub struct ProgramsProcessor<'a> {
     pub linked_program: AddressedProgram<'a>,
     pub export_map: ExportMap<'a>,
     pub inverted_import_map: ExportMap<'a>,
+    pub modules: Vec<ModuleLayout<'a>>,
 }
 
 impl<'a> ProgramsProcessor<'a> {
-    pub fn process(programs: Vec<AddressedProgram<'a>>) -> Result<Self, MvnReportError> {
-        let mut processed_programs: Vec<AddressedProgram> = Vec::new();
-        let mut base: AddressPosition = 0;
+    /// Relocatable code is laid out around absolute code unless given its `bases`
+    pub fn process(
+        programs: Vec<(&'a str, AddressedProgram<'a>)>,
+        bases: Option<Vec<AddressPosition>>,
+    ) -> Result<Self, MvnReportError> {
+        let bases = match bases {
+            Some(bases) => bases,
+            None => Self::allocate_bases(&programs)?,
+        };
+        let mut processed_programs: Vec<(&str, AddressedProgram)> = Vec::new();
         let mut export_map = ExportMap::new();
         let mut imports = BTreeSet::<RelocatableLabel>::new();
-        for program in programs {
-            let processor = ProgramProcessor::process(base, program)?;
-            base = processor.program.get_last_position() + 0x2;
-            processed_programs.push(processor.program);
-            Self::extend_export_unique(&mut export_map, processor.export_map);
+        let mut modules = Vec::new();
+        for ((name, program), base) in programs.into_iter().zip(bases) {
+            let size = program.relocatable_size();
+            let processor =
+                ProgramProcessor::process(base, program).map_err(|e| e.with_file(name))?;
+            processor
+                .program
+                .check_address_space(ErrorCode::ProgramOutsideMemory)
+                .map_err(|e| e.with_file(name))?;
+            modules.push(ModuleLayout {
+                name,
+                base,
+                size,
+                exports: processor
+                    .export_map
+                    .keys()
+                    .map(|export| export.label)
+                    .collect(),
+                weak_exports: processor
+                    .export_map
+                    .keys()
+                    .filter(|export| export.weak)
+                    .map(|export| export.label)
+                    .collect(),
+                imports: processor
+                    .import_map
+                    .values()
+                    .map(|import| import.label)
+                    .collect(),
+            });
+            processed_programs.push((name, processor.program));
+            Self::extend_export_map(&mut export_map, processor.export_map)
+                .map_err(|e| e.with_file(name))?;
             imports.extend(processor.import_map.into_values());
         }
+        Self::check_overlaps(&processed_programs)?;
         let inverted_import_map: BTreeMap<_, _> = imports
             .into_iter()
             .filter(|label| !export_map.contains_key(label))
             .enumerate()
             .map(|(i, label)| (label, u32::try_from(i).unwrap()))
             .collect();
-        let merged_program = Self::merge_programs(processed_programs);
-        let linked_program = match Self::replace_imported_operands_with_positions(
-            merged_program,
-            &export_map,
-            &inverted_import_map,
-        ) {
-            Ok(program) => program,
-            Err(e) => return Err(e),
-        };
+        let mut resolved_programs: Vec<AddressedProgram> = Vec::new();
+        for (name, program) in processed_programs {
+            let program = Self::replace_imported_operands_with_positions(
+                program,
+                &export_map,
+                &inverted_import_map,
+            )
+            .map_err(|e| e.with_file(name))?;
+            resolved_programs.push(program);
+        }
+        let linked_program = Self::merge_programs(resolved_programs);
         Ok(ProgramsProcessor {
             linked_program,
             export_map,
             inverted_import_map,
+            modules,
         })
     }
 
+    /*
+     * Absolute code stays where it was assembled, so the relocatable
+     * code of each program is placed at the first addresses after the
+     * previous program that are not taken by absolute code of any
+     */
+    fn allocate_bases(
+        programs: &[(&str, AddressedProgram<'a>)],
+    ) -> Result<Vec<AddressPosition>, MvnReportError> {
+        let mut taken: Vec<(AddressPosition, AddressPosition)> = programs
+            .iter()
+            .flat_map(|(_, program)| program.absolute_ranges())
+            .collect();
+        let mut bases = Vec::new();
+        let mut next_base = 0;
+        for (name, program) in programs {
+            let size = program.relocatable_size();
+            let base = first_free_base(&taken, next_base, size);
+            if base + size > MEMORY_END + 1 {
+                let line = program
+                    .lines
+                    .iter()
+                    .find(|line| line.address.value.properties.line_relocatable)
+                    .unwrap_or(&program.lines[0]);
+                return Err(MvnReportError::new(
+                    line.address.position,
+                    Some(Message::NoRoomForProgram(size).to_string()),
+                )
+                .with_code(ErrorCode::NoRoomForProgram)
+                .with_span(line.address.span)
+                .with_file(name));
+            }
+            taken.push((base, base + size));
+            bases.push(base);
+            next_base = base + size;
+        }
+        Ok(bases)
+    }
+
+    fn check_overlaps(programs: &[(&str, AddressedProgram<'a>)]) -> Result<(), MvnReportError> {
+        let words = programs
+            .iter()
+            .flat_map(|(name, program)| program.lines.iter().map(move |line| (*name, line)));
+        match first_overlap(words) {
+            Some([(other_name, other_line), (name, line)]) => {
+                let label = MvnReportLabel::new(
+                    Some(other_name.to_owned()),
+                    other_line.address.position,
+                    Message::OtherWordPlacedHere.to_string(),
+                )
+                .with_span(other_line.address.span);
+                Err(MvnReportError::new(
+                    line.address.position,
+                    Some(Message::OverlappingWords(line.address.value.position).to_string()),
+                )
+                .with_code(ErrorCode::OverlappingPrograms)
+                .with_span(line.address.span)
+                .with_file(name)
+                .with_label(label))
+            }
+            None => Ok(()),
+        }
+    }
+
     fn merge_programs(programs: Vec<AddressedProgram<'a>>) -> AddressedProgram<'a> {
         let mut lines: Vec<AddressedLine> = Vec::new();
-        for program in programs {
+        let mut labels: Vec<LabelRecord> = Vec::new();
+        for mut program in programs {
+            labels.append(&mut program.labels);
             lines.extend(program);
         }
-        AddressedProgram::new(lines)
+        AddressedProgram::new(lines).with_labels(labels)
     }
 
     // TODO Refactor, possibly splitting into 2-3 functions
     fn replace_imported_operands_with_positions(
-        program: AddressedProgram<'a>,
+        mut program: AddressedProgram<'a>,
         export_map: &ExportMap<'a>,
         inverted_import_map: &ExportMap<'a>,
     ) -> Result<AddressedProgram<'a>, MvnReportError> {
+        let labels = std::mem::take(&mut program.labels);
         let mut lines: Vec<AddressedLine> = Vec::new();
         for line in program {
             let line = if line.address.value.properties.operand_imported {
@@ -122,14 +254,16 @@ impl<'a> ProgramsProcessor<'a> {
                     Operand::Numeric(_) => {
                         return Err(MvnReportError::new(
                             line.operation.operand.position,
-                            Some("can't replace numeric operand with position".to_owned()),
-                        ))
+                            Some(Message::InvalidImportedOperand.to_string()),
+                        )
+                        .with_code(ErrorCode::InvalidImportedOperand)
+                        .with_span(line.operation.operand.span))
                     }
                     Operand::Symbolic(label) => label,
                 };
-                // TODO Add relocatable field to Label to remove this clone
-                // and the RelocatableLabel struct
-                let operand: RelocatableLabel = operand.clone().into();
+                // TODO Add relocatable field to Label to remove
+                // the RelocatableLabel struct
+                let operand: RelocatableLabel = operand.into();
                 let (position, operand_relocatable, operand_imported) =
                     if let Some((relocatable_label, position)) = export_map.get_key_value(&operand)
                     {
@@ -141,8 +275,10 @@ impl<'a> ProgramsProcessor<'a> {
                     } else {
                         return Err(MvnReportError::new(
                             line.operation.operand.position,
-                            Some("operand marked as imported but not imported".to_owned()),
-                        ));
+                            Some(Message::OperandNotImported.to_string()),
+                        )
+                        .with_code(ErrorCode::OperandNotImported)
+                        .with_span(line.operation.operand.span));
                     };
                 let properties = MachineAddressProperties {
                     operand_relocatable,
@@ -153,9 +289,11 @@ impl<'a> ProgramsProcessor<'a> {
                     properties,
                     ..line.address.value
                 };
-                let address = Token::new(line.address.position, address);
+                let address =
+                    Token::new(line.address.position, address).with_span(line.address.span);
                 let operand: Operand = (*position).into();
-                let operand = Token::new(line.operation.operand.position, operand);
+                let operand = Token::new(line.operation.operand.position, operand)
+                    .with_span(line.operation.operand.span);
                 let operation = Operation {
                     operand,
                     ..line.operation
@@ -170,15 +308,37 @@ impl<'a> ProgramsProcessor<'a> {
             };
             lines.push(line);
         }
-        Ok(AddressedProgram::new(lines))
+        Ok(AddressedProgram::new(lines).with_labels(labels))
     }
 
-    fn extend_export_unique(original_map: &mut ExportMap<'a>, new_map: ExportMap<'a>) {
+    /*
+     * A symbol may only be exported by a single program, unless all
+     * but one of them export it as weak, in which case the other one
+     * is kept. Among weak exports only, the first one is kept
+     */
+    fn extend_export_map(
+        original_map: &mut ExportMap<'a>,
+        new_map: ExportMap<'a>,
+    ) -> Result<(), MvnReportError> {
         for (key, value) in new_map.into_iter() {
-            if original_map.insert(key, value).is_some() {
-                panic!("export map already contained label");
+            match original_map.get_key_value(&key) {
+                Some((original, _)) if !original.weak && !key.weak => {
+                    return Err(MvnReportError::new(
+                        key.label.position(),
+                        Some(Message::DuplicateExport(key.label.name()).to_string()),
+                    )
+                    .with_code(ErrorCode::DuplicateExport)
+                    .with_span(key.label.span()));
+                }
+                Some((original, _)) if !original.weak || key.weak => continue,
+                _ => {
+                    // Keys compare by label only, so the weak one must be removed first
+                    original_map.remove(&key);
+                    original_map.insert(key, value);
+                }
             }
         }
+        Ok(())
     }
 }
 
@@ -193,11 +353,13 @@ impl<'a> ProgramProcessor<'a> {
         base: AddressPosition,
         program: AddressedProgram<'a>,
     ) -> Result<Self, MvnReportError> {
-        let program = program.relocate(base);
+        let mut program = program.relocate(base);
+        let labels = std::mem::take(&mut program.labels);
         let (imports, exports, instructions) = program.partition();
         let import_map = Self::create_import_map(imports);
         let export_map = Self::create_export_map(exports);
-        let program = Self::replace_imported_operands_with_labels(instructions, &import_map)?;
+        let program = Self::replace_imported_operands_with_labels(instructions, &import_map)?
+            .with_labels(labels);
         Ok(ProgramProcessor {
             program,
             export_map,
@@ -219,8 +381,18 @@ impl<'a> ProgramProcessor<'a> {
         let mut export_map = ExportMap::new();
         for line in exports.into_iter() {
             // TODO Review API to replace `line.destruct()`
+            let weak = line
+                .relational_annotation
+                .as_ref()
+                .is_some_and(|annotation| {
+                    annotation.operation.instruction.value
+                        == Instruction::Relational(RelationalMneumonic::WeakExport)
+                });
             let (label, position, relocatable) = line.destruct();
-            export_map.insert(RelocatableLabel::new(relocatable, label), position);
+            export_map.insert(
+                RelocatableLabel::new(relocatable, label).with_weak(weak),
+                position,
+            );
         }
         export_map
     }
@@ -237,20 +409,25 @@ impl<'a> ProgramProcessor<'a> {
                     Operand::Symbolic(_) => {
                         return Err(MvnReportError::new(
                             line.operation.operand.position,
-                            Some("can't replace numeric operand with position".to_owned()),
-                        ))
+                            Some(Message::InvalidImportedOperand.to_string()),
+                        )
+                        .with_code(ErrorCode::InvalidImportedOperand)
+                        .with_span(line.operation.operand.span))
                     }
                 };
                 let operand = if let Some(relocatable_label) = import_map.get(&operand) {
                     Token::new(
                         line.operation.operand.position,
-                        relocatable_label.label.clone().into(),
+                        relocatable_label.label.into(),
                     )
+                    .with_span(line.operation.operand.span)
                 } else {
                     return Err(MvnReportError::new(
                         line.operation.operand.position,
-                        Some("operand marked as imported but not imported".to_owned()),
-                    ));
+                        Some(Message::OperandNotImported.to_string()),
+                    )
+                    .with_code(ErrorCode::OperandNotImported)
+                    .with_span(line.operation.operand.span));
                 };
                 let operation = Operation {
                     operand,
@@ -0,0 +1,376 @@
+use std::path::Path;
+
+use utils::code::ErrorCode;
+use utils::error::{MvnReportError, MvnReportLabel};
+use utils::i18n::Message;
+use utils::io::Source;
+use utils::types::{AddressPosition, Position, SourceSpan};
+
+use crate::types::MEMORY_END;
+
+// Instructions take a word, so inputs start at even addresses unless told otherwise
+const DEFAULT_ALIGNMENT: AddressPosition = 0x2;
+const WILDCARD: &str = "*";
+
+/// Word of a linker script, along with where it was written
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Word<'a> {
+    text: &'a str,
+    position: Position,
+    span: SourceSpan,
+}
+
+impl Word<'_> {
+    fn error(&self, code: ErrorCode, message: Message) -> MvnReportError {
+        MvnReportError::new(self.position, Some(message.to_string()))
+            .with_code(code)
+            .with_span(self.span)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    fn address(&self) -> Result<AddressPosition, MvnReportError> {
+        self.text
+            .strip_prefix('/')
+            .and_then(|address| AddressPosition::from_str_radix(address, 16).ok())
+            .ok_or_else(|| self.error(ErrorCode::MalformedLinkerScript, Message::InvalidImmediate))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Region<'a> {
+    name: Word<'a>,
+    start: AddressPosition,
+    // Inclusive, so that regions may end at the last address of the memory
+    end: AddressPosition,
+    alignment: AddressPosition,
+    inputs: Vec<Word<'a>>,
+}
+
+impl<'a> Region<'a> {
+    fn parse(words: &[Word<'a>]) -> Result<Self, MvnReportError> {
+        let (name, start, end, alignment) = match words {
+            [_, name, start, end] => (name, start, end, None),
+            [_, name, start, end, keyword, alignment] if keyword.is_keyword("ALIGN") => {
+                (name, start, end, Some(alignment))
+            }
+            _ => {
+                return Err(words[0].error(ErrorCode::MalformedLinkerScript, Message::InvalidRegion))
+            }
+        };
+        let (end_word, start, end) = (end, start.address()?, end.address()?);
+        if end < start {
+            return Err(end_word.error(
+                ErrorCode::MalformedLinkerScript,
+                Message::RegionEndsBeforeStart,
+            ));
+        }
+        if end > MEMORY_END {
+            return Err(end_word.error(ErrorCode::RegionOverflow, Message::AddressOutsideMemory));
+        }
+        let alignment = match alignment {
+            Some(word) => match word.address()? {
+                0 => {
+                    return Err(word.error(ErrorCode::MalformedLinkerScript, Message::InvalidRegion))
+                }
+                alignment => alignment,
+            },
+            None => DEFAULT_ALIGNMENT,
+        };
+        Ok(Self {
+            name: *name,
+            start,
+            end,
+            alignment,
+            inputs: Vec::new(),
+        })
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+/*
+ * Places inputs at given addresses instead of laying them end to end
+ * from 0. Scripts declare regions, each followed by the inputs placed
+ * in it one after the other, with `*` standing for every input that is
+ * not listed anywhere else. Comments start with `;`, as in assembly:
+ *
+ *     REGION <name> <start> <end> [ALIGN <alignment>]
+ *     <input>
+ *     *
+ *
+ * Inputs left out of every region keep being laid out end to end,
+ * right after the last input placed. Inputs are placed whole, as
+ * intermediate code has no sections to tell code and data apart
+ */
+#[derive(Debug, PartialEq)]
+pub struct LinkerScript<'a> {
+    name: &'a str,
+    regions: Vec<Region<'a>>,
+}
+
+impl<'a> LinkerScript<'a> {
+    pub fn parse(source: Source<'a>) -> Result<Self, MvnReportError> {
+        Self::parse_regions(source.content)
+            .map(|regions| Self {
+                name: source.name,
+                regions,
+            })
+            .map_err(|e| e.with_file(source.name))
+    }
+
+    fn parse_regions(content: &'a str) -> Result<Vec<Region<'a>>, MvnReportError> {
+        let mut regions: Vec<Region> = Vec::new();
+        let mut offset = 0;
+        for (text, line) in content.split_inclusive('\n').zip(1..) {
+            let words = words(text, line, offset);
+            offset += text.len();
+            match words.as_slice() {
+                [] => continue,
+                [keyword, ..] if keyword.is_keyword("REGION") => {
+                    let region = Region::parse(&words)?;
+                    if let Some(other) = regions.iter().find(|other| other.overlaps(&region)) {
+                        let label = MvnReportLabel::new(
+                            None,
+                            other.name.position,
+                            Message::RegionDeclaredHere.to_string(),
+                        )
+                        .with_span(other.name.span);
+                        return Err(region
+                            .name
+                            .error(
+                                ErrorCode::OverlappingRegions,
+                                Message::RegionOverlaps(other.name.text),
+                            )
+                            .with_label(label));
+                    }
+                    regions.push(region);
+                }
+                [input] => match regions.last_mut() {
+                    Some(region) => region.inputs.push(*input),
+                    None => {
+                        return Err(
+                            input.error(ErrorCode::MalformedLinkerScript, Message::ExpectedRegion)
+                        )
+                    }
+                },
+                [_, unexpected, ..] => {
+                    return Err(unexpected.error(
+                        ErrorCode::MalformedLinkerScript,
+                        Message::UnexpectedWord(unexpected.text),
+                    ))
+                }
+            }
+        }
+        Ok(regions)
+    }
+
+    /// Base address of each input, given its name and the space it takes
+    pub fn place(
+        &self,
+        inputs: &[(&str, AddressPosition)],
+    ) -> Result<Vec<AddressPosition>, MvnReportError> {
+        let listed: Vec<bool> = inputs
+            .iter()
+            .map(|(name, _)| {
+                self.regions
+                    .iter()
+                    .flat_map(|region| region.inputs.iter())
+                    .any(|input| matches(name, input.text))
+            })
+            .collect();
+        let mut bases: Vec<Option<AddressPosition>> = vec![None; inputs.len()];
+        for region in self.regions.iter() {
+            let mut next_base = region.start;
+            for input in region.inputs.iter() {
+                if input.text != WILDCARD && !listed_input_exists(inputs, input.text) {
+                    return Err(input
+                        .error(
+                            ErrorCode::MalformedLinkerScript,
+                            Message::UnknownInput(input.text),
+                        )
+                        .with_file(self.name));
+                }
+                for (i, (name, size)) in inputs.iter().enumerate() {
+                    let placed = match input.text {
+                        WILDCARD => !listed[i],
+                        pattern => matches(name, pattern),
+                    };
+                    if !placed || bases[i].is_some() {
+                        continue;
+                    }
+                    let base = align(next_base, region.alignment);
+                    if base + size > region.end + 1 {
+                        return Err(input
+                            .error(
+                                ErrorCode::RegionOverflow,
+                                Message::InputDoesNotFit(name, region.name.text),
+                            )
+                            .with_file(self.name));
+                    }
+                    bases[i] = Some(base);
+                    next_base = base + size;
+                }
+            }
+        }
+
+        let mut next_base = std::iter::zip(&bases, inputs)
+            .filter_map(|(base, (_, size))| base.map(|base| base + size))
+            .max()
+            .unwrap_or(0);
+        Ok(std::iter::zip(bases, inputs)
+            .map(|(base, (_, size))| {
+                base.unwrap_or_else(|| {
+                    let base = align(next_base, DEFAULT_ALIGNMENT);
+                    next_base = base + size;
+                    base
+                })
+            })
+            .collect())
+    }
+}
+
+fn listed_input_exists(inputs: &[(&str, AddressPosition)], pattern: &str) -> bool {
+    inputs.iter().any(|(name, _)| matches(name, pattern))
+}
+
+// Inputs may be referred to by their path, file name or archive member name
+fn matches(name: &str, pattern: &str) -> bool {
+    name == pattern
+        || Path::new(name)
+            .file_name()
+            .is_some_and(|file| file == pattern)
+        || name.ends_with(&format!("({pattern})"))
+}
+
+fn align(address: AddressPosition, alignment: AddressPosition) -> AddressPosition {
+    address.div_ceil(alignment) * alignment
+}
+
+// Splits a line into its words, leaving comments out
+fn words(text: &str, line: u32, offset: usize) -> Vec<Word<'_>> {
+    let text = text.split(';').next().unwrap_or_default();
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(word_start), true) => {
+                words.push(Word {
+                    text: &text[word_start..i],
+                    position: Position::new(line, word_start + 1),
+                    span: SourceSpan::new(offset + word_start, offset + i),
+                });
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn parse(content: &str) -> Result<LinkerScript<'_>, MvnReportError> {
+        LinkerScript::parse(Source::new("link.ld", content))
+    }
+
+    const TEST_SCRIPT: &str = indoc! {"
+        ; Vector table at the start of the memory
+        REGION VECTORS /000 /00F
+        vectors.int
+        REGION DATA /100 /1FF ALIGN /10
+        data.int
+        table.int
+        REGION CODE /200 /FFF
+        *
+    "};
+
+    #[test]
+    fn should_place_inputs() {
+        let script = parse(TEST_SCRIPT).unwrap();
+        let inputs = [
+            ("main.int", 0x20),
+            ("lib/data.int", 0x06),
+            ("vectors.int", 0x08),
+            ("lib.mvnar(table.int)", 0x04),
+            ("subroutine.int", 0x0A),
+        ];
+        assert_eq!(
+            script.place(&inputs).unwrap(),
+            vec![0x200, 0x100, 0x000, 0x110, 0x220]
+        );
+    }
+
+    #[test]
+    fn unlisted_inputs_should_follow_placed_ones() {
+        let script = parse("REGION CODE /100 /1FF\nmain.int\n").unwrap();
+        let inputs = [("main.int", 0x11), ("subroutine.int", 0x04)];
+        assert_eq!(script.place(&inputs).unwrap(), vec![0x100, 0x112]);
+    }
+
+    #[test]
+    fn should_report_inputs_not_fitting_region() {
+        let script = parse(TEST_SCRIPT).unwrap();
+        let error = script.place(&[("vectors.int", 0x12)]).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::RegionOverflow));
+        assert_eq!(error.position, Position::new(3, 1));
+        assert_eq!(error.file.as_deref(), Some("link.ld"));
+    }
+
+    #[test]
+    fn should_report_unknown_inputs() {
+        let script = parse(TEST_SCRIPT).unwrap();
+        let error = script.place(&[("main.int", 0x12)]).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::MalformedLinkerScript));
+        assert_eq!(error.position, Position::new(3, 1));
+    }
+
+    #[test]
+    fn should_report_overlapping_regions() {
+        let error = parse(indoc! {"
+            REGION DATA /100 /1FF
+            REGION CODE /180 /FFF
+        "})
+        .unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::OverlappingRegions));
+        assert_eq!(error.position, Position::new(2, 8));
+        assert_eq!(error.span.len(), 4);
+        assert_eq!(error.labels[0].position, Position::new(1, 8));
+    }
+
+    #[test]
+    fn should_report_regions_past_memory() {
+        let error = parse("REGION CODE /100 /1000").unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::RegionOverflow));
+        assert_eq!(error.position, Position::new(1, 18));
+    }
+
+    #[test]
+    fn should_report_malformed_script() {
+        for (script, line) in [
+            ("main.int", 1),
+            ("REGION CODE /100", 1),
+            ("REGION CODE /200 /100", 1),
+            ("REGION CODE /100 /200 ALIGN /0", 1),
+            ("REGION CODE 100 /200", 1),
+            ("REGION CODE /100 /200\nmain.int sub.int", 2),
+        ] {
+            let error = parse(script).unwrap_err();
+            assert_eq!(
+                error.code,
+                Some(ErrorCode::MalformedLinkerScript),
+                "{script}"
+            );
+            assert_eq!(error.position.line, line, "{script}");
+        }
+    }
+}
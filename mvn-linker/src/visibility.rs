@@ -0,0 +1,196 @@
+use utils::code::ErrorCode;
+use utils::error::MvnReportError;
+use utils::i18n::Message;
+
+use crate::processor::program::{ExportMap, ModuleLayout, ProgramsProcessor, RelocatableLabel};
+use crate::types::Label;
+
+/*
+ * Controls which symbols a partially linked program exposes to later
+ * linking steps, so that libraries don't leak nor clash over the names
+ * of their helpers. Exports may be restricted to a whitelist or hidden
+ * one by one, after which the exports left and unresolved imports may
+ * be renamed. Symbols that aren't there are ignored, as there's nothing
+ * to hide or rename
+ */
+#[derive(Debug, Default)]
+pub struct Visibility<'s> {
+    pub exports: Option<Vec<&'s str>>,
+    pub hidden: Vec<&'s str>,
+    pub renames: Vec<(&'s str, &'s str)>,
+}
+
+impl<'s> Visibility<'s> {
+    pub fn apply<'a>(
+        &self,
+        processor: ProgramsProcessor<'a>,
+    ) -> Result<ProgramsProcessor<'a>, MvnReportError>
+    where
+        's: 'a,
+    {
+        let ProgramsProcessor {
+            linked_program,
+            mut export_map,
+            inverted_import_map,
+            mut modules,
+        } = processor;
+        export_map.retain(|export, _| self.is_visible(export.label.name()));
+
+        let export_map = self.rename_all(export_map, &ExportMap::new(), &modules)?;
+        let inverted_import_map = self.rename_all(inverted_import_map, &export_map, &modules)?;
+        for module in modules.iter_mut() {
+            let labels = module
+                .exports
+                .iter_mut()
+                .chain(module.weak_exports.iter_mut())
+                .chain(module.imports.iter_mut());
+            for label in labels {
+                *label = self.renamed(*label);
+            }
+        }
+
+        Ok(ProgramsProcessor {
+            linked_program,
+            export_map,
+            inverted_import_map,
+            modules,
+        })
+    }
+
+    fn is_visible(&self, symbol: &str) -> bool {
+        let whitelisted = match &self.exports {
+            Some(exports) => exports.contains(&symbol),
+            None => true,
+        };
+        whitelisted && !self.hidden.contains(&symbol)
+    }
+
+    fn renamed<'a>(&self, label: Label<'a>) -> Label<'a>
+    where
+        's: 'a,
+    {
+        match self.renames.iter().find(|(old, _)| *old == label.name()) {
+            Some((_, new)) => Label::new(new),
+            None => label,
+        }
+    }
+
+    // Symbols in `taken` have already been renamed, so their names can't be reused
+    fn rename_all<'a>(
+        &self,
+        map: ExportMap<'a>,
+        taken: &ExportMap<'a>,
+        modules: &[ModuleLayout],
+    ) -> Result<ExportMap<'a>, MvnReportError>
+    where
+        's: 'a,
+    {
+        let mut renamed_map = ExportMap::new();
+        for (symbol, value) in map {
+            let renamed = RelocatableLabel {
+                label: self.renamed(symbol.label),
+                ..symbol
+            };
+            if renamed_map.contains_key(&renamed) || taken.contains_key(&renamed) {
+                return Err(conflict(symbol.label, renamed.label, modules));
+            }
+            renamed_map.insert(renamed, value);
+        }
+        Ok(renamed_map)
+    }
+}
+
+// Reported where the symbol was declared, which is only known before renaming
+fn conflict(original: Label, renamed: Label, modules: &[ModuleLayout]) -> MvnReportError {
+    let error = MvnReportError::new(
+        original.position(),
+        Some(Message::SymbolNameConflict(renamed.name()).to_string()),
+    )
+    .with_code(ErrorCode::SymbolNameConflict)
+    .with_span(original.span());
+    let module = modules.iter().find(|module| {
+        module
+            .exports
+            .iter()
+            .chain(module.imports.iter())
+            .any(|label| label.name() == original.name())
+    });
+    match module {
+        Some(module) => error.with_file(module.name),
+        None => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::processor::process;
+    use utils::io::Source;
+
+    const LIBRARY: &str = indoc! {"
+        6000 0000 ; > PRINT
+        6000 0002 ; > HELPER
+        1000 0000 ; < PUTC
+        4000 A002 ; PRINT   SC  HELPER
+        4002 0000 ; HELPER  $   /1
+        5004 A000 ;         SC  PUTC
+        6006 B002 ;         RS  HELPER
+    "};
+
+    fn symbols(processor: &ProgramsProcessor) -> (Vec<String>, Vec<String>) {
+        let names = |map: &ExportMap| map.keys().map(|key| key.label.to_string()).collect();
+        (
+            names(&processor.export_map),
+            names(&processor.inverted_import_map),
+        )
+    }
+
+    #[test]
+    fn should_keep_whitelisted_exports() {
+        let programs = [Source::new("library.int", LIBRARY)];
+        let processor = process(&programs, &[], None, None, false, false).unwrap();
+        let visibility = Visibility {
+            exports: Some(vec!["PRINT"]),
+            ..Default::default()
+        };
+        let processor = visibility.apply(processor).unwrap();
+        assert_eq!(
+            symbols(&processor),
+            (vec!["PRINT".to_owned()], vec!["PUTC".to_owned()])
+        );
+    }
+
+    #[test]
+    fn should_hide_and_rename_symbols() {
+        let programs = [Source::new("library.int", LIBRARY)];
+        let processor = process(&programs, &[], None, None, false, false).unwrap();
+        let visibility = Visibility {
+            hidden: vec!["HELPER"],
+            renames: vec![("PRINT", "LIB_PRINT"), ("PUTC", "OS_PUTC")],
+            ..Default::default()
+        };
+        let processor = visibility.apply(processor).unwrap();
+        assert_eq!(
+            symbols(&processor),
+            (vec!["LIB_PRINT".to_owned()], vec!["OS_PUTC".to_owned()])
+        );
+        assert_eq!(processor.modules[0].exports[1].name(), "LIB_PRINT");
+    }
+
+    #[test]
+    fn should_report_conflicting_names() {
+        let programs = [Source::new("library.int", LIBRARY)];
+        let visibility = Visibility {
+            renames: vec![("PUTC", "PRINT")],
+            ..Default::default()
+        };
+        let processor = process(&programs, &[], None, None, false, false).unwrap();
+        let error = visibility.apply(processor).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::SymbolNameConflict));
+        assert_eq!(error.position.line, 3);
+        assert_eq!(error.file.as_deref(), Some("library.int"));
+    }
+}
@@ -1,4 +1,10 @@
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use machine_code::object::ObjectProgram;
 use utils::error::MvnReportError;
+use utils::i18n::Message;
+use utils::io::Source;
+use utils::report::{exit_with_errors, MessageFormat};
 
 use crate::processor::program::{ProgramsProcessor, RelocatableLabel};
 use crate::types::AddressPosition;
@@ -7,43 +13,96 @@ use crate::types::{
     MachineAddressProperties, Operand, Operation,
 };
 
-pub fn print(processor_output: Result<ProgramsProcessor, MvnReportError>, complete_linkage: bool) {
+/// Format in which the linked program is written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dialect {
+    #[default]
+    Mvn,
+    Json,
+}
+
+// Implemented by hand so that the help of each value is translated
+impl ValueEnum for Dialect {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Mvn, Self::Json]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Mvn => PossibleValue::new("mvn").help(Message::HelpFormatMvn.to_string()),
+            Self::Json => PossibleValue::new("json").help(Message::HelpFormatJson.to_string()),
+        })
+    }
+}
+
+pub fn print(
+    processor_output: Result<ProgramsProcessor, MvnReportError>,
+    sources: &[Source],
+    complete_linkage: bool,
+    dialect: Dialect,
+    message_format: MessageFormat,
+) {
     match processor_output {
-        Ok(processor) => print_program(processor, complete_linkage),
-        Err(error) => print_error(error),
+        Ok(processor) => print_program(processor, complete_linkage, dialect),
+        Err(error) => exit_with_errors(&[error], sources, message_format),
     }
 }
 
-fn print_error(error: MvnReportError) {
-    eprintln!("{error:#?}");
+fn print_program(processor: ProgramsProcessor, complete_linkage: bool, dialect: Dialect) {
+    print!("{}", render(processor, complete_linkage, dialect));
 }
 
-fn print_program(processor: ProgramsProcessor, complete_linkage: bool) {
-    for line in processor.linked_program {
-        println!("{line}");
+/// Linked program in the given dialect, followed by its symbol table on partial
+/// linkage. Labels placing no word are only written as text, as JSON objects
+/// have no place for them
+pub fn render(
+    mut processor: ProgramsProcessor,
+    complete_linkage: bool,
+    dialect: Dialect,
+) -> String {
+    let labels = std::mem::take(&mut processor.linked_program.labels);
+    let lines = linked_lines(processor, complete_linkage);
+    match dialect {
+        Dialect::Mvn => lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .chain(labels.iter().map(|label| format!("{label}\n")))
+            .collect(),
+        Dialect::Json => format!("{}\n", ObjectProgram::new(&lines).to_json()),
     }
+}
+
+fn linked_lines(processor: ProgramsProcessor, complete_linkage: bool) -> Vec<AddressedLine> {
+    let mut lines: Vec<AddressedLine> = processor.linked_program.into_iter().collect();
 
     if complete_linkage {
-        return;
+        return lines;
     }
 
     for (export_label, export_position) in processor.export_map.into_iter() {
         let RelocatableLabel {
             relocatable,
+            weak,
             label: export_label,
         } = export_label;
+        // Weak exports stay weak, so that later linking steps may still override them
+        let mneumonic = match weak {
+            true => RelationalMneumonic::WeakExport,
+            false => RelationalMneumonic::Export,
+        };
         let line = relational_label_position_to_line(
             export_label,
             relocatable,
             export_position,
-            RelationalMneumonic::Export,
+            mneumonic,
         );
-        println!("{line}");
+        lines.push(line);
     }
 
     for (import_label, import_position) in processor.inverted_import_map.into_iter() {
         let RelocatableLabel {
             relocatable: _,
+            weak: _,
             label: import_label,
         } = import_label;
         let line = relational_label_position_to_line(
@@ -52,16 +111,17 @@ fn print_program(processor: ProgramsProcessor, complete_linkage: bool) {
             import_position,
             RelationalMneumonic::Import,
         );
-        println!("{line}");
+        lines.push(line);
     }
+    lines
 }
 
 fn relational_label_position_to_line(
-    label: Label,
+    label: Label<'_>,
     relocatable: bool,
     position: AddressPosition,
     mneumonic: RelationalMneumonic,
-) -> AddressedLine {
+) -> AddressedLine<'_> {
     let imported = mneumonic == RelationalMneumonic::Import;
     AddressedLine::new(
         MachineAddress::new(
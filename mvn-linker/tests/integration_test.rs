@@ -1,6 +1,12 @@
 use indoc::indoc;
 
+use mvn_linker::archive::{Archive, Member};
+use mvn_linker::object::ObjectProgram;
 use mvn_linker::processor::process;
+use mvn_linker::script::LinkerScript;
+use mvn_linker::types::Operand;
+use utils::code::ErrorCode;
+use utils::io::Source;
 
 // TODO Improve test to test actual output on a buffer
 // Requires modifying `writer` to write in any buffer and now just print to stdout
@@ -29,7 +35,417 @@ fn partial_link_main_and_subroutine() {
     "};
 
     let complete_linkage = false;
-    let programs = vec![main_program, subroutine_program];
-    let processor = process(programs, complete_linkage);
+    let programs = [
+        Source::new("main.int", main_program),
+        Source::new("subroutine.int", subroutine_program),
+    ];
+    let processor = process(&programs, &[], None, None, false, complete_linkage);
     assert!(processor.is_ok())
 }
+
+#[test]
+fn duplicate_export_should_fail() {
+    let main_program = indoc! {"
+        0000 0000 ; > INIT
+        0000 3000 ; INIT    LV  /0
+    "};
+    let library_program = indoc! {"
+        0000 0000 ; > INIT
+        0000 3001 ; INIT    LV  /1
+    "};
+
+    let programs = [
+        Source::new("main.int", main_program),
+        Source::new("library.int", library_program),
+    ];
+    let error = process(&programs, &[], None, None, false, false).unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::DuplicateExport));
+    assert_eq!(error.file.as_deref(), Some("library.int"));
+}
+
+#[test]
+fn should_link_only_needed_archive_members() {
+    let main_program = indoc! {"
+        1000 0000 ; < ADD_TWO
+        0000 3003 ; MAIN    LV  /3
+        1002 A000 ;         SC  ADD_TWO
+        0004 C000 ;         HM  /0
+    "};
+    let members = vec![
+        Member::new(
+            "subroutine.int".to_owned(),
+            indoc! {"
+                6100 0102 ; > ADD_TWO
+                5000 0000 ; < RESULT
+                4100 0002 ; TWO         K   /2
+                4102 0000 ; ADD_TWO     $   /1
+                6104 4100 ;             AD  TWO
+                5106 9000 ;             MM  RESULT
+                6108 B102 ;             RS  ADD_TWO
+            "}
+            .to_owned(),
+        ),
+        Member::new(
+            "unused.int".to_owned(),
+            indoc! {"
+                4000 0000 ; > UNUSED
+                4000 C000 ; UNUSED  HM  /0
+            "}
+            .to_owned(),
+        ),
+        Member::new(
+            "result.int".to_owned(),
+            indoc! {"
+                4000 0000 ; > RESULT
+                4000 0000 ; RESULT  K   /0
+            "}
+            .to_owned(),
+        ),
+    ];
+    let archives = [Archive::new(members).unwrap()];
+
+    let programs = [Source::new("main.int", main_program)];
+    let processor = process(&programs, &archives, None, None, false, true).unwrap();
+    let exports: Vec<&str> = processor
+        .export_map
+        .keys()
+        .map(|export| export.label.name())
+        .collect();
+    // `RESULT` is only imported by the member resolving `ADD_TWO`
+    assert_eq!(exports, vec!["ADD_TWO", "RESULT"]);
+    assert!(processor.inverted_import_map.is_empty());
+    assert_eq!(processor.linked_program.lines.len(), 3 + 5 + 1);
+}
+
+#[test]
+fn should_place_programs_with_script() {
+    let main_program = indoc! {"
+        1000 0000 ; < ADD_TWO
+        4000 3003 ; MAIN    LV  /3
+        5002 A000 ;         SC  ADD_TWO
+        4004 C000 ;         HM  /0
+    "};
+    let subroutine_program = indoc! {"
+        6000 0002 ; > ADD_TWO
+        4000 0002 ; TWO         K   /2
+        4002 0000 ; ADD_TWO     $   /1
+        6004 4000 ;             AD  TWO
+        6006 B002 ;             RS  ADD_TWO
+    "};
+    let script = indoc! {"
+        REGION CODE /100 /1FF
+        main.int
+        REGION LIBRARY /400 /4FF ALIGN /10
+        subroutine.int
+    "};
+
+    let programs = [
+        Source::new("main.int", main_program),
+        Source::new("subroutine.int", subroutine_program),
+    ];
+    let script = LinkerScript::parse(Source::new("link.ld", script)).unwrap();
+    let processor = process(&programs, &[], Some(&script), None, false, true).unwrap();
+    let lines: Vec<String> = processor
+        .linked_program
+        .into_iter()
+        .map(|line| line.to_string())
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "4100 3003",
+            "6102 A402",
+            "4104 C000",
+            "4400 0002",
+            "4402 0000",
+            "6404 4400",
+            "6406 B402",
+        ]
+    );
+}
+
+#[test]
+fn overlapping_programs_should_fail() {
+    let main_program = indoc! {"
+        0000 3003 ; MAIN    LV  /3
+        0002 C000 ;         HM  /0
+    "};
+    let table_program = indoc! {"
+        0002 0001 ; TABLE   K   /1
+        0004 0002 ;         K   /2
+    "};
+
+    let programs = [
+        Source::new("main.int", main_program),
+        Source::new("table.int", table_program),
+    ];
+    let error = process(&programs, &[], None, None, false, false).unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::OverlappingPrograms));
+    assert_eq!(error.file.as_deref(), Some("table.int"));
+    assert_eq!(error.position.line, 1);
+    assert_eq!(error.labels[0].file.as_deref(), Some("main.int"));
+    assert_eq!(error.labels[0].position.line, 2);
+}
+
+#[test]
+fn programs_past_memory_should_fail() {
+    let main_program = indoc! {"
+        0000 3003 ; MAIN    LV  /3
+        0002 C000 ;         HM  /0
+    "};
+    let subroutine_program = indoc! {"
+        4000 0002 ; TWO     K   /2
+        6002 4FFE ;         AD  LAST
+    "};
+
+    let programs = [
+        Source::new("main.int", main_program),
+        Source::new("subroutine.int", subroutine_program),
+    ];
+    let error = process(&programs, &[], None, None, false, false).unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::ProgramOutsideMemory));
+    assert_eq!(error.file.as_deref(), Some("subroutine.int"));
+    assert_eq!(error.position.line, 2);
+}
+
+#[test]
+fn complete_linkage_with_unresolved_symbols_should_fail() {
+    let main_program = indoc! {"
+        1000 0000 ; < ADD_TWO
+        1000 0001 ; < RESULT
+        4000 3003 ; MAIN    LV  /3
+        5002 A000 ;         SC  ADD_TWO
+        5004 9001 ;         MM  RESULT
+        5006 A000 ;         SC  ADD_TWO
+        4008 C000 ;         HM  /0
+    "};
+    let result_program = indoc! {"
+        4000 0000 ; > RESULT
+        4000 0000 ; RESULT  K   /0
+    "};
+
+    let programs = [
+        Source::new("main.int", main_program),
+        Source::new("result.int", result_program),
+    ];
+    let error = process(&programs, &[], None, None, false, true).unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::UnresolvedSymbols));
+    assert_eq!(error.file.as_deref(), Some("main.int"));
+    assert_eq!(error.position.line, 1);
+    let lines: Vec<u32> = error
+        .labels
+        .iter()
+        .map(|label| label.position.line)
+        .collect();
+    assert_eq!(lines, vec![4, 6]);
+    assert_eq!(
+        error.help,
+        vec![
+            "undefined reference to ADD_TWO in main.int:4".to_owned(),
+            "undefined reference to ADD_TWO in main.int:6".to_owned(),
+        ]
+    );
+}
+
+#[test]
+fn strong_export_should_override_weak_one() {
+    let default_program = indoc! {"
+        6000 0000 ; >? PRINT
+        4000 C000 ; PRINT   HM  /0
+    "};
+    let main_program = indoc! {"
+        1000 0000 ; < PRINT
+        5000 A000 ;         SC  PRINT
+    "};
+    let print_program = indoc! {"
+        6000 0000 ; > PRINT
+        4000 B000 ; PRINT   RS  PRINT
+    "};
+
+    for programs in [
+        [
+            Source::new("default.int", default_program),
+            Source::new("main.int", main_program),
+            Source::new("print.int", print_program),
+        ],
+        [
+            Source::new("print.int", print_program),
+            Source::new("main.int", main_program),
+            Source::new("default.int", default_program),
+        ],
+    ] {
+        let processor = process(&programs, &[], None, None, false, true).unwrap();
+        let print_position = processor
+            .modules
+            .iter()
+            .find(|module| module.name == "print.int")
+            .unwrap()
+            .base;
+        let call = processor
+            .linked_program
+            .lines
+            .iter()
+            .find(|line| line.to_string().contains(" A"))
+            .unwrap();
+        assert_eq!(
+            call.operation.operand.value,
+            Operand::Numeric(print_position)
+        );
+    }
+}
+
+#[test]
+fn weak_exports_should_not_conflict() {
+    let default_program = indoc! {"
+        6000 0000 ; >? PRINT
+        4000 C000 ; PRINT   HM  /0
+    "};
+
+    let programs = [
+        Source::new("default.int", default_program),
+        Source::new("other.int", default_program),
+    ];
+    let processor = process(&programs, &[], None, None, false, false).unwrap();
+    let (export, position) = processor.export_map.iter().next().unwrap();
+    assert!(export.weak);
+    assert_eq!(*position, 0);
+}
+
+#[test]
+fn relocatable_code_should_be_placed_around_absolute_code() {
+    let main_program = indoc! {"
+        4000 3003 ; MAIN    LV  /3
+        4002 C000 ;         HM  /0
+    "};
+    let data_program = indoc! {"
+        0002 0000 ; FLAGS   K   /0
+        4000 0001 ; ONE     K   /1
+    "};
+
+    let programs = [
+        Source::new("main.int", main_program),
+        Source::new("data.int", data_program),
+    ];
+    let processor = process(&programs, &[], None, None, false, true).unwrap();
+    let lines: Vec<String> = processor
+        .linked_program
+        .into_iter()
+        .map(|line| line.to_string())
+        .collect();
+    assert_eq!(
+        lines,
+        vec!["4004 3003", "4006 C000", "0002 0000", "4008 0001"]
+    );
+}
+
+#[test]
+fn relocatable_code_without_room_should_fail() {
+    let main_program = indoc! {"
+        4000 3003 ; MAIN    LV  /3
+        4FFC C000 ;         HM  /0
+    "};
+    let vectors_program = indoc! {"
+        0004 0000 ;         JP  /0
+    "};
+
+    let programs = [
+        Source::new("main.int", main_program),
+        Source::new("vectors.int", vectors_program),
+    ];
+    let error = process(&programs, &[], None, None, false, true).unwrap_err();
+    assert_eq!(error.code, Some(ErrorCode::NoRoomForProgram));
+    assert_eq!(error.file.as_deref(), Some("main.int"));
+    assert_eq!(error.position.line, 1);
+}
+
+#[test]
+fn debug_records_should_follow_relocated_words() {
+    let main_program = indoc! {"
+        4000 C000 ; ! MAIN.ASM:1:9
+    "};
+    let library_program = indoc! {"
+        6000 8002 ; ! LIBRARY.ASM:2:1 LOAD
+        4002 0001 ; ! LIBRARY.ASM:3:1 ONE
+        4004 ; ! LIBRARY.ASM:4:1 BUFFER
+    "};
+
+    let programs = [
+        Source::new("main.int", main_program),
+        Source::new("library.int", library_program),
+    ];
+    let processor = process(&programs, &[], None, None, false, true).unwrap();
+    let labels: Vec<String> = processor
+        .linked_program
+        .labels
+        .iter()
+        .map(|label| label.to_string())
+        .collect();
+    assert_eq!(labels, vec!["4006 ; ! LIBRARY.ASM:4:1 BUFFER"]);
+    let lines: Vec<String> = processor
+        .linked_program
+        .into_iter()
+        .map(|line| line.to_string())
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "4000 C000 ; ! MAIN.ASM:1:9",
+            "6002 8004 ; ! LIBRARY.ASM:2:1 LOAD",
+            "4004 0001 ; ! LIBRARY.ASM:3:1 ONE",
+        ]
+    );
+}
+
+#[test]
+fn should_link_json_objects() {
+    let main_object = indoc! {r#"
+        {
+          "symbols": [{ "kind": "import", "name": "ONE", "value": 0 }],
+          "words": [
+            { "address": 0, "instruction": 8, "operand": 0, "operand_imported": true },
+            { "address": 2, "instruction": 12, "operand": 0 }
+          ]
+        }
+    "#};
+    let library_program = indoc! {"
+        2000 0000 ; > ONE
+        4000 0001 ; ONE K /1
+    "};
+
+    let main_program = ObjectProgram::from_json(main_object).unwrap().to_string();
+    let programs = [
+        Source::new("main.json", &main_program),
+        Source::new("library.int", library_program),
+    ];
+    let processor = process(&programs, &[], None, None, false, true).unwrap();
+    let object = ObjectProgram::new(&processor.linked_program.lines);
+    assert_eq!(object.words.len(), 3);
+    assert_eq!(object.words[0].operand, 4);
+    assert!(!object.words[0].operand_imported);
+}
+
+#[test]
+fn errors_on_json_objects_should_point_to_json() {
+    let main_object = indoc! {r#"
+        {
+          "symbols": [{ "kind": "import", "name": "ONE", "value": 0 }],
+          "words": [
+            { "address": 0, "instruction": 8, "operand": 0, "operand_imported": true }
+          ]
+        }
+    "#};
+
+    let main_program = ObjectProgram::from_json(main_object)
+        .unwrap()
+        .to_text(main_object);
+    let programs = [Source::new("main.json", &main_program.text).with_map(&main_program.map)];
+    let error = process(&programs, &[], None, None, false, true)
+        .err()
+        .unwrap()
+        .locate(&main_program.map, Some("main.json"));
+    assert_eq!(error.code, Some(ErrorCode::UnresolvedSymbols));
+    assert_eq!(error.position.line, 2);
+    assert_eq!(&main_object[error.span.start..error.span.end], "\"ONE\"");
+    assert_eq!(error.labels[0].position.line, 4);
+    assert_eq!(error.help, ["undefined reference to ONE in main.json:4"]);
+}
@@ -0,0 +1,295 @@
+use assembler::processor::address::{Address, AddressedLine, AddressedProgram};
+use assembler::processor::process;
+use assembler::writer::encode;
+use assembly::parser::Parse;
+use assembly::types::mneumonic::{NormalMneumonic, PositionalMneumonic, RelationalMneumonic};
+use assembly::types::{Instruction, Operand, Program};
+use utils::error::MvnReportError;
+use utils::i18n::Message;
+use utils::types::SourceSpan;
+
+/*
+ * What the language server knows about a source: the errors reported
+ * by the assembler and where each label is defined and referred to.
+ * Sources are uppercased as by the command line tools, though only
+ * their ASCII letters, so that offsets into them still hold
+ */
+#[derive(Debug, Default)]
+pub struct Analysis {
+    pub diagnostics: Vec<MvnReportError>,
+    pub occurrences: Vec<Occurrence>,
+    pub symbols: Vec<Symbol>,
+    pub words: Vec<Word>,
+    /// Whether the source could be parsed, as otherwise nothing but diagnostics is known
+    pub parsed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OccurrenceKind {
+    Definition,
+    Import,
+    Export,
+    Reference,
+}
+
+/// Label as written in the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    pub name: String,
+    pub span: SourceSpan,
+    pub kind: OccurrenceKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Code,
+    Data,
+    Import,
+}
+
+/// Label defined in the source, either on a line or by an import
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub span: SourceSpan,
+    /// Extent of the line defining it
+    pub line_span: SourceSpan,
+    pub address: Address,
+}
+
+/// Line placing a word in memory, along with its address and word as in `.int` files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Word {
+    pub span: SourceSpan,
+    pub address: u32,
+    pub word: u32,
+}
+
+impl Analysis {
+    pub fn new(text: &str) -> Self {
+        let text = text.to_ascii_uppercase();
+        let diagnostics = process(&text).err().unwrap_or_default();
+        let Ok((_, program)) = Program::parse_assembler(text.as_str().into()) else {
+            return Self {
+                diagnostics,
+                ..Default::default()
+            };
+        };
+        let program = AddressedProgram::process(program);
+        let label_map = program.map_labels();
+
+        let mut analysis = Self {
+            diagnostics,
+            parsed: true,
+            ..Default::default()
+        };
+        for AddressedLine { address, line } in program.lines.iter() {
+            let instruction = line.operation.instruction.value;
+            if let Some(label) = &line.label {
+                let kind = match instruction {
+                    Instruction::Normal(NormalMneumonic::SetConstant)
+                    | Instruction::Positional(PositionalMneumonic::ReserveMemory) => {
+                        SymbolKind::Data
+                    }
+                    _ => SymbolKind::Code,
+                };
+                let span = label.value.span();
+                analysis.define(label.value.name(), span, kind, line.span(), address);
+                analysis.occur(label.value.name(), span, OccurrenceKind::Definition);
+            }
+            if let Operand::Symbolic(label) = &line.operation.operand.value {
+                let kind = match instruction {
+                    Instruction::Relational(RelationalMneumonic::Import) => {
+                        let address = label_map.get(label).unwrap_or(address);
+                        let kind = SymbolKind::Import;
+                        analysis.define(label.name(), label.span(), kind, line.span(), address);
+                        OccurrenceKind::Import
+                    }
+                    Instruction::Relational(_) => OccurrenceKind::Export,
+                    _ => OccurrenceKind::Reference,
+                };
+                analysis.occur(label.name(), label.span(), kind);
+            }
+            if let Some((word_address, word)) = encode(address, line, &label_map) {
+                analysis.words.push(Word {
+                    span: line.span(),
+                    address: word_address,
+                    word,
+                });
+            }
+        }
+        analysis
+    }
+
+    fn define(
+        &mut self,
+        name: &str,
+        span: SourceSpan,
+        kind: SymbolKind,
+        line_span: SourceSpan,
+        address: &Address,
+    ) {
+        self.symbols.push(Symbol {
+            name: name.to_owned(),
+            kind,
+            span,
+            line_span,
+            address: address.clone(),
+        });
+    }
+
+    fn occur(&mut self, name: &str, span: SourceSpan, kind: OccurrenceKind) {
+        self.occurrences.push(Occurrence {
+            name: name.to_owned(),
+            span,
+            kind,
+        });
+    }
+
+    // Offsets right past a label still refer to it, as where the cursor is left after typing it
+    pub fn occurrence_at(&self, offset: usize) -> Option<&Occurrence> {
+        self.occurrences
+            .iter()
+            .find(|occurrence| (occurrence.span.start..=occurrence.span.end).contains(&offset))
+    }
+
+    pub fn symbol(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|symbol| symbol.name == name)
+    }
+
+    pub fn references<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'s Occurrence> {
+        self.occurrences
+            .iter()
+            .filter(move |occurrence| occurrence.name == name)
+    }
+
+    pub fn exported(&self, name: &str) -> bool {
+        self.references(name)
+            .any(|occurrence| occurrence.kind == OccurrenceKind::Export)
+    }
+
+    pub fn word_at(&self, offset: usize) -> Option<&Word> {
+        self.words
+            .iter()
+            .find(|word| (word.span.start..=word.span.end).contains(&offset))
+    }
+
+    /// Address of the label at `offset` and the word of its line, along with their extent
+    pub fn hover(&self, offset: usize) -> Option<(SourceSpan, String)> {
+        let occurrence = self.occurrence_at(offset);
+        let word = self.word_at(offset);
+        let mut contents = Vec::new();
+        if let Some(symbol) = occurrence.and_then(|occurrence| self.symbol(&occurrence.name)) {
+            contents.push(self.describe(symbol));
+        }
+        if let Some(word) = word {
+            let word = format!("{:04X} {:04X}", word.address, word.word);
+            contents.push(Message::HoverWord(&word).to_string());
+        }
+        if contents.is_empty() {
+            return None;
+        }
+        let span = occurrence
+            .map(|occurrence| occurrence.span)
+            .or(word.map(|word| word.span))?;
+        Some((span, contents.join("\n\n")))
+    }
+
+    fn describe(&self, symbol: &Symbol) -> String {
+        let position = symbol.address.position;
+        if symbol.kind == SymbolKind::Import {
+            return Message::HoverImport(&symbol.name, position).to_string();
+        }
+        let description = Message::HoverLabel(&symbol.name, position).to_string();
+        let properties: Vec<String> = [
+            (symbol.address.relocatable, Message::HoverRelocatable),
+            (self.exported(&symbol.name), Message::HoverExported),
+        ]
+        .into_iter()
+        .filter_map(|(set, property)| set.then_some(property.to_string()))
+        .collect();
+        match properties.is_empty() {
+            true => description,
+            false => format!("{description} ({})", properties.join(", ")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const PROGRAM: &str = indoc! {"
+                < print
+                > main
+                &   /0
+        main    lv  one
+                sc  print
+                hm  main
+        one     k   /1
+                # main
+    "};
+
+    fn offset(pattern: &str) -> usize {
+        PROGRAM.find(pattern).unwrap()
+    }
+
+    #[test]
+    fn should_find_definitions_and_references() {
+        let analysis = Analysis::new(PROGRAM);
+        assert!(analysis.parsed);
+        assert!(analysis.diagnostics.is_empty());
+
+        let occurrence = analysis.occurrence_at(offset("one\n")).unwrap();
+        assert_eq!(occurrence.name, "ONE");
+        let symbol = analysis.symbol("ONE").unwrap();
+        assert_eq!(symbol.kind, SymbolKind::Data);
+        assert_eq!(symbol.span.start, offset("one "));
+
+        let kinds: Vec<OccurrenceKind> = analysis
+            .references("MAIN")
+            .map(|occurrence| occurrence.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                OccurrenceKind::Export,
+                OccurrenceKind::Definition,
+                OccurrenceKind::Reference,
+                OccurrenceKind::Reference,
+            ]
+        );
+        assert_eq!(analysis.symbol("PRINT").unwrap().kind, SymbolKind::Import);
+    }
+
+    #[test]
+    fn should_describe_label_and_word() {
+        let analysis = Analysis::new(PROGRAM);
+        let reference = offset("sc  print") + 4;
+        let (span, contents) = analysis.hover(reference).unwrap();
+        assert_eq!(span.start, reference);
+        assert_eq!(
+            contents,
+            "`PRINT` imported as symbol 0\n\nassembled into `5002 A000`"
+        );
+        let (_, contents) = analysis.hover(offset("main ")).unwrap();
+        assert_eq!(
+            contents,
+            "`MAIN` at /000 (relocatable, exported)\n\nassembled into `6000 3006`"
+        );
+    }
+
+    #[test]
+    fn invalid_source_should_only_have_diagnostics() {
+        let analysis = Analysis::new("        lv  foo\n");
+        assert!(analysis.parsed);
+        assert_eq!(analysis.diagnostics.len(), 1);
+
+        let analysis = Analysis::new("        lv\n");
+        assert!(!analysis.parsed);
+        assert_eq!(analysis.diagnostics.len(), 1);
+    }
+}
@@ -0,0 +1,160 @@
+use lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+    Position, Range, Url,
+};
+use utils::error::{MvnReportError, Severity};
+use utils::types::SourceSpan;
+
+use crate::analysis::{Analysis, Symbol};
+
+/*
+ * Source open in the editor. Editors locate text by line and UTF-16
+ * code unit, while the assembler does it by byte offset, so every
+ * location is converted on its way in and out
+ */
+pub struct Document {
+    pub text: String,
+    pub version: i32,
+    pub analysis: Analysis,
+    /// Symbols of the last source that could be parsed, still offered
+    /// for completion while the line being typed is incomplete
+    pub known_symbols: Vec<Symbol>,
+}
+
+impl Document {
+    pub fn new(text: String, version: i32) -> Self {
+        let mut document = Self {
+            text: String::new(),
+            version,
+            analysis: Analysis::default(),
+            known_symbols: Vec::new(),
+        };
+        document.update(text, version);
+        document
+    }
+
+    pub fn update(&mut self, text: String, version: i32) {
+        self.analysis = Analysis::new(&text);
+        if self.analysis.parsed {
+            self.known_symbols = self.analysis.symbols.clone();
+        }
+        self.text = text;
+        self.version = version;
+    }
+
+    pub fn offset(&self, position: Position) -> usize {
+        let mut offset = 0;
+        for (index, line) in self.text.split_inclusive('\n').enumerate() {
+            if index as u32 == position.line {
+                let mut character = 0;
+                for (start, char) in line.trim_end_matches(['\r', '\n']).char_indices() {
+                    if character >= position.character {
+                        return offset + start;
+                    }
+                    character += char.len_utf16() as u32;
+                }
+                return offset + line.trim_end_matches(['\r', '\n']).len();
+            }
+            offset += line.len();
+        }
+        self.text.len()
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let before = self.text.get(..offset).unwrap_or(&self.text);
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Position::new(
+            before.matches('\n').count() as u32,
+            before[line_start..].encode_utf16().count() as u32,
+        )
+    }
+
+    pub fn range(&self, span: SourceSpan) -> Range {
+        Range::new(self.position(span.start), self.position(span.end))
+    }
+
+    // Errors without an extent are placed where they were found, spanning no text
+    fn error_range(&self, position: utils::types::Position, span: SourceSpan) -> Range {
+        if !span.is_empty() {
+            return self.range(span);
+        }
+        let line = position.line.saturating_sub(1) as usize;
+        let line_start: usize = self
+            .text
+            .split_inclusive('\n')
+            .take(line)
+            .map(str::len)
+            .sum();
+        let offset = line_start + position.column.saturating_sub(1);
+        let position = self.position(offset.min(self.text.len()));
+        Range::new(position, position)
+    }
+
+    pub fn diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        self.analysis
+            .diagnostics
+            .iter()
+            .map(|error| self.diagnostic(error, uri))
+            .collect()
+    }
+
+    fn diagnostic(&self, error: &MvnReportError, uri: &Url) -> Diagnostic {
+        let mut message = error.message.clone().unwrap_or_default();
+        for help in error.help.iter() {
+            message.push_str(&format!("\nhelp: {help}"));
+        }
+        let related_information = error
+            .labels
+            .iter()
+            .map(|label| DiagnosticRelatedInformation {
+                location: Location::new(uri.clone(), self.error_range(label.position, label.span)),
+                message: label.message.clone(),
+            })
+            .collect::<Vec<_>>();
+        Diagnostic {
+            range: self.error_range(error.position, error.span),
+            severity: Some(match error.severity {
+                Severity::Error => DiagnosticSeverity::ERROR,
+                Severity::Warning => DiagnosticSeverity::WARNING,
+            }),
+            code: error
+                .code
+                .map(|code| NumberOrString::String(code.code().to_owned())),
+            source: Some("mvn".to_owned()),
+            message,
+            related_information: (!related_information.is_empty()).then_some(related_information),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_convert_positions_and_offsets() {
+        let document = Document::new("; ação\n  LV /0\n".to_owned(), 0);
+        let offset = document.text.find("LV").unwrap();
+        assert_eq!(document.position(offset), Position::new(1, 2));
+        assert_eq!(document.offset(Position::new(1, 2)), offset);
+        // Characters outside ASCII take more bytes than UTF-16 code units
+        assert_eq!(document.position(7), Position::new(0, 5));
+        assert_eq!(document.offset(Position::new(0, 5)), 7);
+        assert_eq!(document.offset(Position::new(0, 80)), 8);
+    }
+
+    #[test]
+    fn should_report_diagnostics() {
+        let document = Document::new("        LV  FOO\n".to_owned(), 0);
+        let uri = Url::parse("file:///main.asm").unwrap();
+        let diagnostics = document.diagnostics(&uri);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(0, 12), Position::new(0, 15))
+        );
+        assert!(diagnostics[0].code.is_some());
+    }
+}
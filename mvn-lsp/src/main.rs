@@ -0,0 +1,13 @@
+mod analysis;
+mod document;
+mod server;
+
+use utils::i18n::{set_lang, Lang};
+
+fn main() {
+    set_lang(Lang::detect(std::env::args()));
+    if let Err(error) = server::run() {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use assembly::types::Instruction;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, References,
+    Request as RequestTrait,
+};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability, Location,
+    MarkupContent, MarkupKind, OneOf, PublishDiagnosticsParams, ReferenceParams,
+    ServerCapabilities, SymbolKind as LspSymbolKind, TextDocumentPositionParams,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+
+use crate::analysis::{Occurrence, OccurrenceKind, SymbolKind};
+use crate::document::Document;
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+pub fn run() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(capabilities())?;
+    connection.initialize(capabilities)?;
+    Server::default().main_loop(&connection)?;
+    // The writer thread only stops once the connection is gone
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Url, Document>,
+}
+
+impl Server {
+    fn main_loop(&mut self, connection: &Connection) -> Result<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    connection
+                        .sender
+                        .send(self.handle_request(request).into())?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(uri) = self.handle_notification(notification) {
+                        connection
+                            .sender
+                            .send(self.publish_diagnostics(uri).into())?;
+                    }
+                }
+                Message::Response(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            GotoDefinition::METHOD => self.respond::<GotoDefinition>(request, Self::definition),
+            References::METHOD => self.respond::<References>(request, Self::references),
+            HoverRequest::METHOD => self.respond::<HoverRequest>(request, Self::hover),
+            Completion::METHOD => self.respond::<Completion>(request, Self::completion),
+            DocumentSymbolRequest::METHOD => {
+                self.respond::<DocumentSymbolRequest>(request, Self::document_symbols)
+            }
+            method => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unhandled method `{method}`"),
+            ),
+        }
+    }
+
+    fn respond<R: RequestTrait>(
+        &self,
+        request: Request,
+        handler: impl Fn(&Self, R::Params) -> R::Result,
+    ) -> Response {
+        match serde_json::from_value::<R::Params>(request.params) {
+            Ok(params) => Response::new_ok(request.id, handler(self, params)),
+            Err(error) => Response::new_err(
+                request.id,
+                ErrorCode::InvalidParams as i32,
+                error.to_string(),
+            ),
+        }
+    }
+
+    /// Keeps track of the open documents, returning the one whose diagnostics changed
+    fn handle_notification(&mut self, notification: Notification) -> Option<Url> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                let document = params.text_document;
+                let uri = document.uri.clone();
+                self.documents
+                    .insert(document.uri, Document::new(document.text, document.version));
+                Some(uri)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                // Documents are synchronized in full, so the last change holds the whole text
+                let text = params.content_changes.into_iter().last()?.text;
+                let uri = params.text_document.uri;
+                let version = params.text_document.version;
+                match self.documents.get_mut(&uri) {
+                    Some(document) => document.update(text, version),
+                    None => {
+                        self.documents
+                            .insert(uri.clone(), Document::new(text, version));
+                    }
+                }
+                Some(uri)
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                self.documents.remove(&params.text_document.uri);
+                Some(params.text_document.uri)
+            }
+            _ => None,
+        }
+    }
+
+    // Closed documents are left without diagnostics
+    fn publish_diagnostics(&self, uri: Url) -> Notification {
+        let document = self.documents.get(&uri);
+        let params = PublishDiagnosticsParams {
+            diagnostics: document.map_or(Vec::new(), |document| document.diagnostics(&uri)),
+            version: document.map(|document| document.version),
+            uri,
+        };
+        Notification::new(PublishDiagnostics::METHOD.to_owned(), params)
+    }
+
+    fn occurrence_at(
+        &self,
+        params: &TextDocumentPositionParams,
+    ) -> Option<(&Document, &Occurrence)> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let offset = document.offset(params.position);
+        let occurrence = document.analysis.occurrence_at(offset)?;
+        Some((document, occurrence))
+    }
+
+    // Imports are where their labels are defined, as far as the source is concerned
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let params = params.text_document_position_params;
+        let (document, occurrence) = self.occurrence_at(&params)?;
+        let symbol = document.analysis.symbol(&occurrence.name)?;
+        let location = Location::new(params.text_document.uri, document.range(symbol.span));
+        Some(GotoDefinitionResponse::Scalar(location))
+    }
+
+    fn references(&self, params: ReferenceParams) -> Option<Vec<Location>> {
+        let include_declaration = params.context.include_declaration;
+        let params = params.text_document_position;
+        let (document, occurrence) = self.occurrence_at(&params)?;
+        let locations = document
+            .analysis
+            .references(&occurrence.name)
+            .filter(|reference| {
+                include_declaration
+                    || !matches!(
+                        reference.kind,
+                        OccurrenceKind::Definition | OccurrenceKind::Import
+                    )
+            })
+            .map(|reference| {
+                Location::new(
+                    params.text_document.uri.clone(),
+                    document.range(reference.span),
+                )
+            })
+            .collect();
+        Some(locations)
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let params = params.text_document_position_params;
+        let document = self.documents.get(&params.text_document.uri)?;
+        let offset = document.offset(params.position);
+        let (span, contents) = document.analysis.hover(offset)?;
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: contents,
+            }),
+            range: Some(document.range(span)),
+        })
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let uri = params.text_document_position.text_document.uri;
+        let document = self.documents.get(&uri)?;
+        let mneumonics = Instruction::ALL.map(|instruction| {
+            let detail = match instruction {
+                Instruction::Normal(mneumonic) => Some(format!("/{:X}", mneumonic)),
+                _ => None,
+            };
+            CompletionItem {
+                label: instruction.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                detail,
+                ..Default::default()
+            }
+        });
+        let labels = document.known_symbols.iter().map(|symbol| CompletionItem {
+            label: symbol.name.clone(),
+            kind: Some(match symbol.kind {
+                SymbolKind::Code => CompletionItemKind::FUNCTION,
+                SymbolKind::Data => CompletionItemKind::VARIABLE,
+                SymbolKind::Import => CompletionItemKind::INTERFACE,
+            }),
+            ..Default::default()
+        });
+        Some(CompletionResponse::Array(
+            mneumonics.into_iter().chain(labels).collect(),
+        ))
+    }
+
+    fn document_symbols(&self, params: DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let analysis = &document.analysis;
+        let symbols = analysis
+            .symbols
+            .iter()
+            .map(|symbol| {
+                let detail = match symbol.kind {
+                    SymbolKind::Import => None,
+                    _ => Some(format!("/{:03X}", symbol.address.position)),
+                };
+                // `deprecated` must still be given, despite being deprecated itself
+                #[allow(deprecated)]
+                DocumentSymbol {
+                    name: symbol.name.clone(),
+                    detail,
+                    kind: match symbol.kind {
+                        SymbolKind::Code => LspSymbolKind::FUNCTION,
+                        SymbolKind::Data => LspSymbolKind::VARIABLE,
+                        SymbolKind::Import => LspSymbolKind::INTERFACE,
+                    },
+                    tags: None,
+                    deprecated: None,
+                    range: document.range(symbol.line_span),
+                    selection_range: document.range(symbol.span),
+                    children: None,
+                }
+            })
+            .collect();
+        Some(DocumentSymbolResponse::Nested(symbols))
+    }
+}
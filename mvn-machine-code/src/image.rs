@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+
+use utils::code::ErrorCode;
+use utils::error::MvnReportError;
+use utils::i18n::Message;
+use utils::types::{Position, SourceSpan};
+
+use crate::types::{AddressPosition, AddressedProgram, MEMORY_END};
+
+/*
+ * Memory images for tools outside the MVN, such as FPGA memory
+ * initializers. Memory is addressed by byte and each word takes
+ * two of them, the most significant first
+ */
+
+const HEX_RECORD_LENGTH: usize = 0x10;
+const HEX_DATA: u8 = 0x00;
+const HEX_END_OF_FILE: u8 = 0x01;
+
+type Memory = BTreeMap<AddressPosition, u8>;
+
+/// Bytes placed in memory by an absolute program, leaving out its symbol table
+pub fn memory(program: &AddressedProgram) -> Memory {
+    let mut memory = Memory::new();
+    for line in program.lines.iter() {
+        if line.relational_annotation.is_some() {
+            continue;
+        }
+        let position = line.address.value.position;
+        let [high, low] = line.word().to_be_bytes();
+        memory.insert(position, high);
+        memory.insert(position + 1, low);
+    }
+    memory
+}
+
+/// Fails on the first word that would still be changed by the linker or the relocator
+pub fn check_absolute(program: &AddressedProgram) -> Result<(), MvnReportError> {
+    let line = program.lines.iter().find(|line| {
+        let properties = line.address.value.properties;
+        line.relational_annotation.is_some()
+            || properties.line_relocatable
+            || properties.operand_relocatable
+            || properties.operand_imported
+    });
+    match line {
+        Some(line) => Err(MvnReportError::new(
+            line.address.position,
+            Some(Message::NotAbsoluteOnConversion(line.address.value.position).to_string()),
+        )
+        .with_code(ErrorCode::NotAbsoluteOnConversion)
+        .with_span(line.address.span)
+        .with_help(Message::RelocateBeforeConverting.to_string())),
+        None => Ok(()),
+    }
+}
+
+pub fn to_intel_hex(program: &AddressedProgram) -> String {
+    let mut records: Vec<(AddressPosition, Vec<u8>)> = Vec::new();
+    for (position, byte) in memory(program) {
+        match records.last_mut() {
+            Some((start, data))
+                if *start + data.len() as AddressPosition == position
+                    && data.len() < HEX_RECORD_LENGTH =>
+            {
+                data.push(byte)
+            }
+            _ => records.push((position, vec![byte])),
+        }
+    }
+    let mut hex = String::new();
+    for (start, data) in records {
+        hex.push_str(&hex_record(start as u16, HEX_DATA, &data));
+    }
+    hex.push_str(&hex_record(0, HEX_END_OF_FILE, &[]));
+    hex
+}
+
+fn hex_record(address: u16, kind: u8, data: &[u8]) -> String {
+    let [high, low] = address.to_be_bytes();
+    let mut bytes = vec![data.len() as u8, high, low, kind];
+    bytes.extend_from_slice(data);
+    let checksum = bytes
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg();
+    bytes.push(checksum);
+    let digits: String = bytes.iter().map(|byte| format!("{byte:02X}")).collect();
+    format!(":{digits}\n")
+}
+
+/// Memory from address 0 up to the last byte of the program, gaps taking `fill`
+pub fn to_binary(program: &AddressedProgram, fill: u8) -> Vec<u8> {
+    let memory = memory(program);
+    let end = memory.keys().next_back().map_or(0, |position| position + 1);
+    (0..end)
+        .map(|position| *memory.get(&position).unwrap_or(&fill))
+        .collect()
+}
+
+/// Input to Verilog's `$readmemh`, for a memory of one byte per address
+pub fn to_verilog(program: &AddressedProgram) -> String {
+    let mut verilog = String::from("// MVN memory image, one byte per address\n");
+    let mut next = None;
+    let mut line_length = 0;
+    for (position, byte) in memory(program) {
+        if next != Some(position) {
+            if line_length > 0 {
+                verilog.push('\n');
+            }
+            verilog.push_str(&format!("@{position:03X}\n"));
+            line_length = 0;
+        } else if line_length == HEX_RECORD_LENGTH {
+            verilog.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            verilog.push(' ');
+        }
+        verilog.push_str(&format!("{byte:02X}"));
+        line_length += 1;
+        next = Some(position + 1);
+    }
+    if line_length > 0 {
+        verilog.push('\n');
+    }
+    verilog
+}
+
+/// VHDL constant initializing a memory of one byte per address
+pub fn to_vhdl(program: &AddressedProgram, fill: u8) -> String {
+    let mut vhdl = String::from("-- MVN memory image, one byte per address\n");
+    vhdl.push_str(&format!(
+        "type mvn_memory is array (0 to {}) of std_logic_vector(7 downto 0);\n",
+        MEMORY_END
+    ));
+    vhdl.push_str("constant MVN_MEMORY : mvn_memory := (\n");
+    for (position, byte) in memory(program) {
+        vhdl.push_str(&format!("    16#{position:03X}# => x\"{byte:02X}\",\n"));
+    }
+    vhdl.push_str(&format!("    others => x\"{fill:02X}\"\n);\n"));
+    vhdl
+}
+
+/// Program in the MVN format from an Intel HEX file, one line per word
+pub fn from_intel_hex(hex: &str) -> Result<String, MvnReportError> {
+    let mut memory = Memory::new();
+    let mut offset = 0;
+    for (index, line) in hex.split_inclusive('\n').enumerate() {
+        let line_start = offset;
+        offset += line.len();
+        let record = line.trim();
+        if record.is_empty() {
+            continue;
+        }
+        let indentation = line.len() - line.trim_start().len();
+        let position = Position::new(index as u32 + 1, indentation + 1);
+        let start = line_start + indentation;
+        let span = SourceSpan::new(start, start + record.len());
+        let error = |message: Message| {
+            MvnReportError::new(position, Some(message.to_string()))
+                .with_code(ErrorCode::InvalidHexRecord)
+                .with_span(span)
+        };
+
+        let bytes = record
+            .strip_prefix(':')
+            .filter(|digits| digits.len() % 2 == 0 && digits.is_ascii())
+            .and_then(|digits| {
+                (0..digits.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+                    .collect::<Option<Vec<u8>>>()
+            })
+            .filter(|bytes| bytes.len() >= 5 && bytes.len() == bytes[0] as usize + 5)
+            .ok_or_else(|| error(Message::MalformedHexRecord))?;
+        let sum = bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        if sum != 0 {
+            return Err(error(Message::HexChecksumMismatch));
+        }
+
+        let address = u16::from_be_bytes([bytes[1], bytes[2]]) as AddressPosition;
+        let data = &bytes[4..bytes.len() - 1];
+        match bytes[3] {
+            HEX_DATA => {
+                for (i, byte) in data.iter().enumerate() {
+                    let position = address + i as AddressPosition;
+                    if position > MEMORY_END {
+                        return Err(error(Message::WordOutsideMemory(position)));
+                    }
+                    memory.insert(position, *byte);
+                }
+            }
+            HEX_END_OF_FILE => break,
+            kind => return Err(error(Message::UnsupportedHexRecord(kind))),
+        }
+    }
+
+    // Words are aligned to even addresses, missing halves taking 0
+    let mut words: BTreeMap<AddressPosition, [u8; 2]> = BTreeMap::new();
+    for (position, byte) in memory {
+        words.entry(position & !0x1).or_default()[(position & 0x1) as usize] = byte;
+    }
+    Ok(words
+        .into_iter()
+        .map(|(position, word)| format!("{position:04X} {:04X}\n", u16::from_be_bytes(word)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parser::Parse;
+
+    fn program(input: &str) -> AddressedProgram<'_> {
+        AddressedProgram::parse_machine_code(input.into())
+            .unwrap()
+            .1
+    }
+
+    const PROGRAM: &str = indoc! {"
+        0000 3012
+        0002 C000
+        0010 0001
+    "};
+
+    #[test]
+    fn should_convert_to_intel_hex() {
+        assert_eq!(
+            to_intel_hex(&program(PROGRAM)),
+            indoc! {"
+                :0400000030 12C0 00FA
+                :020010000001ED
+                :00000001FF
+            "}
+            .replace(' ', "")
+        );
+    }
+
+    #[test]
+    fn should_convert_from_intel_hex() {
+        let hex = to_intel_hex(&program(PROGRAM));
+        assert_eq!(from_intel_hex(&hex).unwrap(), PROGRAM);
+    }
+
+    #[test]
+    fn invalid_checksum_should_fail() {
+        let error = from_intel_hex(":020010000001EE\n").unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::InvalidHexRecord));
+    }
+
+    #[test]
+    fn should_convert_to_binary() {
+        assert_eq!(
+            to_binary(&program("0000 3012\n0004 C000\n"), 0xFF),
+            [0x30, 0x12, 0xFF, 0xFF, 0xC0, 0x00]
+        );
+    }
+
+    #[test]
+    fn should_convert_to_verilog() {
+        assert_eq!(
+            to_verilog(&program(PROGRAM)),
+            indoc! {"
+                // MVN memory image, one byte per address
+                @000
+                30 12 C0 00
+                @010
+                00 01
+            "}
+        );
+    }
+
+    #[test]
+    fn should_convert_to_vhdl() {
+        let vhdl = to_vhdl(&program("0010 0001\n"), 0x00);
+        assert!(vhdl.contains("    16#010# => x\"00\",\n    16#011# => x\"01\",\n"));
+        assert!(vhdl.ends_with("    others => x\"00\"\n);\n"));
+    }
+
+    #[test]
+    fn relocatable_program_should_fail() {
+        assert!(check_absolute(&program(PROGRAM)).is_ok());
+        let error = check_absolute(&program("4000 0000\n")).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::NotAbsoluteOnConversion));
+    }
+}
@@ -1,2 +1,5 @@
+pub mod image;
+#[cfg(feature = "serde")]
+pub mod object;
 pub mod parser;
 pub mod types;
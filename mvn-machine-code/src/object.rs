@@ -0,0 +1,496 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utils::code::ErrorCode;
+use utils::error::MvnReportError;
+use utils::i18n::Message;
+use utils::types::{Position, SourceMap, SourceSpan};
+
+use crate::types::mneumonic::RelationalMneumonic;
+use crate::types::{AddressPosition, AddressedLine, Instruction, Operand, MEMORY_END};
+
+/*
+ * Owned counterpart of `AddressedProgram`, which borrows from the text
+ * it was parsed from. Besides being stored or sent across threads, it
+ * is written and read as JSON, an alternative to the text format
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ObjectProgram {
+    pub symbols: Vec<ObjectSymbol>,
+    pub words: Vec<ObjectWord>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Export,
+    WeakExport,
+    Import,
+}
+
+/// Entry of the symbol table: the address of an export or the index of an import
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectSymbol {
+    pub kind: SymbolKind,
+    pub name: String,
+    pub value: AddressPosition,
+    #[serde(default)]
+    pub relocatable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectWord {
+    pub address: AddressPosition,
+    pub instruction: u8,
+    pub operand: u32,
+    #[serde(default)]
+    pub line_relocatable: bool,
+    #[serde(default)]
+    pub operand_relocatable: bool,
+    #[serde(default)]
+    pub operand_imported: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<ObjectDebug>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectDebug {
+    pub file: String,
+    pub line: u32,
+    pub column: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl ObjectProgram {
+    pub fn new<'l, 'a: 'l>(lines: impl IntoIterator<Item = &'l AddressedLine<'a>>) -> Self {
+        let mut program = Self::default();
+        for line in lines {
+            let properties = line.address.value.properties;
+            match &line.relational_annotation {
+                Some(annotation) => {
+                    let kind = match annotation.operation.instruction.value {
+                        Instruction::Relational(RelationalMneumonic::Import) => SymbolKind::Import,
+                        Instruction::Relational(RelationalMneumonic::WeakExport) => {
+                            SymbolKind::WeakExport
+                        }
+                        _ => SymbolKind::Export,
+                    };
+                    let value = match line.operation.operand.value {
+                        Operand::Numeric(value) => value,
+                        Operand::Symbolic(_) => 0,
+                    };
+                    program.symbols.push(ObjectSymbol {
+                        kind,
+                        name: annotation.operation.operand.value.to_string(),
+                        value,
+                        relocatable: properties.operand_relocatable,
+                    });
+                }
+                None => {
+                    let word = line.word();
+                    program.words.push(ObjectWord {
+                        address: line.address.value.position,
+                        instruction: (word >> 12) as u8,
+                        operand: u32::from(word & 0xFFF),
+                        line_relocatable: properties.line_relocatable,
+                        operand_relocatable: properties.operand_relocatable,
+                        operand_imported: properties.operand_imported,
+                        debug: line.debug.map(|debug| ObjectDebug {
+                            file: debug.file.to_owned(),
+                            line: debug.line,
+                            column: debug.column,
+                            label: debug.label.map(|label| label.to_string()),
+                        }),
+                    });
+                }
+            }
+        }
+        program
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("object programs are always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, MvnReportError> {
+        let program: Self = serde_json::from_str(json).map_err(|error| {
+            // The position is already reported, so it is left out of the message
+            let message = error.to_string();
+            let message = message.split(" at line ").next().unwrap_or_default();
+            MvnReportError::new(
+                Position::new(error.line() as u32, error.column()),
+                Some(Message::MalformedObject(message).to_string()),
+            )
+            .with_code(ErrorCode::MalformedObject)
+        })?;
+        program.check_ranges(json)?;
+        Ok(program)
+    }
+
+    // Values wider than their field would spill over the others once written as text
+    fn check_ranges(&self, json: &str) -> Result<(), MvnReportError> {
+        let spans = ObjectSpans::new(json);
+        let symbols = self
+            .symbols
+            .iter()
+            .zip(&spans.symbols)
+            .map(|(symbol, entry)| (entry, [("value", symbol.value, MEMORY_END)].to_vec()));
+        let words = self.words.iter().zip(&spans.words).map(|(word, entry)| {
+            let fields = [
+                ("address", word.address, MEMORY_END),
+                ("instruction", u32::from(word.instruction), 0xF),
+                ("operand", word.operand, 0xFFF),
+            ];
+            (entry, fields.to_vec())
+        });
+        for (entry, fields) in symbols.chain(words) {
+            for (field, value, max) in fields {
+                if value <= max {
+                    continue;
+                }
+                let (start, end) = entry.get(field);
+                let message = Message::ObjectValueTooLarge(field, value, max).to_string();
+                return Err(MvnReportError::new(
+                    position_at(json, start),
+                    Some(Message::MalformedObject(&message).to_string()),
+                )
+                .with_code(ErrorCode::MalformedObject)
+                .with_span(SourceSpan::new(start, end)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn position_at(text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Position::new(
+        before.matches('\n').count() as u32 + 1,
+        offset - line_start + 1,
+    )
+}
+
+/*
+ * Where the values of each symbol and word lie in the JSON they were read
+ * from, found by a scan that trusts the JSON to be well-formed, as
+ * `serde_json` has already read it. Ranges are byte offsets
+ */
+struct ObjectSpans<'a> {
+    symbols: Vec<EntrySpans<'a>>,
+    words: Vec<EntrySpans<'a>>,
+}
+
+struct EntrySpans<'a> {
+    entry: (usize, usize),
+    fields: Vec<(&'a str, (usize, usize))>,
+}
+
+impl<'a> ObjectSpans<'a> {
+    fn new(json: &'a str) -> Self {
+        let mut spans = Self {
+            symbols: Vec::new(),
+            words: Vec::new(),
+        };
+        for (key, (start, _)) in members(json, skip_whitespace(json, 0)) {
+            let list = match key {
+                "symbols" => &mut spans.symbols,
+                "words" => &mut spans.words,
+                _ => continue,
+            };
+            for entry in elements(json, start) {
+                list.push(EntrySpans {
+                    entry,
+                    fields: members(json, entry.0),
+                });
+            }
+        }
+        spans
+    }
+}
+
+impl EntrySpans<'_> {
+    // Fields left to their default point to the whole entry
+    fn get(&self, field: &str) -> (usize, usize) {
+        self.fields
+            .iter()
+            .find(|(key, _)| *key == field)
+            .map_or(self.entry, |(_, span)| *span)
+    }
+}
+
+fn skip_whitespace(json: &str, offset: usize) -> usize {
+    json[offset..]
+        .find(|c: char| !c.is_ascii_whitespace())
+        .map_or(json.len(), |start| offset + start)
+}
+
+// End of the value starting at `offset`
+fn skip_value(json: &str, offset: usize) -> usize {
+    let bytes = json.as_bytes();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, &byte) in bytes.iter().enumerate().skip(offset) {
+        match (in_string, byte) {
+            (true, _) if escaped => escaped = false,
+            (true, b'\\') => escaped = true,
+            (true, b'"') => {
+                in_string = false;
+                if depth == 0 {
+                    return index + 1;
+                }
+            }
+            (true, _) => {}
+            (false, b'"') => in_string = true,
+            (false, b'{' | b'[') => depth += 1,
+            (false, b'}' | b']') if depth == 0 => return index,
+            (false, b'}' | b']') => {
+                depth -= 1;
+                if depth == 0 {
+                    return index + 1;
+                }
+            }
+            (false, b',') if depth == 0 => return index,
+            (false, byte) if depth == 0 && byte.is_ascii_whitespace() => return index,
+            (false, _) => {}
+        }
+    }
+    json.len()
+}
+
+// Values of an array starting at `offset`, or none when it isn't one
+fn elements(json: &str, offset: usize) -> Vec<(usize, usize)> {
+    let mut elements = Vec::new();
+    if !json[offset..].starts_with('[') {
+        return elements;
+    }
+    let mut offset = skip_whitespace(json, offset + 1);
+    while offset < json.len() && !json[offset..].starts_with(']') {
+        let end = skip_value(json, offset);
+        elements.push((offset, end));
+        offset = skip_whitespace(json, end);
+        if json[offset..].starts_with(',') {
+            offset = skip_whitespace(json, offset + 1);
+        }
+    }
+    elements
+}
+
+// Keys and values of an object starting at `offset`, or none when it isn't one
+fn members(json: &str, offset: usize) -> Vec<(&str, (usize, usize))> {
+    let mut members = Vec::new();
+    if !json[offset..].starts_with('{') {
+        return members;
+    }
+    let mut offset = skip_whitespace(json, offset + 1);
+    while offset < json.len() && json[offset..].starts_with('"') {
+        let key_end = skip_value(json, offset);
+        let key = &json[offset + 1..key_end - 1];
+        // Past the colon
+        let start = skip_whitespace(json, skip_whitespace(json, key_end) + 1);
+        let end = skip_value(json, start);
+        members.push((key, (start, end)));
+        offset = skip_whitespace(json, end);
+        if json[offset..].starts_with(',') {
+            offset = skip_whitespace(json, offset + 1);
+        }
+    }
+    members
+}
+
+/// Symbol table first, as written by the assembler
+impl fmt::Display for ObjectProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for fields in self.text_lines() {
+            for (field, _) in fields {
+                write!(f, "{field}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Object written as text, along with where each field came from in its JSON
+pub struct ObjectText {
+    pub text: String,
+    pub map: SourceMap,
+}
+
+// Pieces of a line of text, along with the key of the JSON value each came from
+type TextLine<'a> = Vec<(String, Option<&'a str>)>;
+
+impl ObjectProgram {
+    /// Program written as text, along with where each of its fields lies in `json`
+    pub fn to_text(&self, json: &str) -> ObjectText {
+        let spans = ObjectSpans::new(json);
+        let mut text = String::new();
+        let mut map = SourceMap::new(json);
+        let entries = spans.symbols.iter().chain(&spans.words);
+        for (fields, entry) in self.text_lines().into_iter().zip(entries) {
+            map.push_line(text.len());
+            for (field, key) in fields {
+                if let Some(key) = key {
+                    map.push_field(text.len(), field.len(), entry.get(key));
+                }
+                text.push_str(&field);
+            }
+            map.push_field(text.len(), 1, (entry.entry.1 - 1, entry.entry.1));
+            text.push('\n');
+        }
+        ObjectText { text, map }
+    }
+
+    fn text_lines(&self) -> Vec<TextLine<'static>> {
+        let mut lines = Vec::new();
+        for symbol in self.symbols.iter() {
+            let (mneumonic, properties) = match symbol.kind {
+                SymbolKind::Export => (RelationalMneumonic::Export, symbol.relocatable as u8 * 2),
+                SymbolKind::WeakExport => (
+                    RelationalMneumonic::WeakExport,
+                    symbol.relocatable as u8 * 2,
+                ),
+                SymbolKind::Import => (RelationalMneumonic::Import, 1),
+            };
+            lines.push(vec![
+                (format!("{properties:X}000"), Some("kind")),
+                (" ".to_owned(), None),
+                (format!("0{:03X}", symbol.value), Some("value")),
+                (" ; ".to_owned(), None),
+                (mneumonic.to_string(), Some("kind")),
+                (" ".to_owned(), None),
+                (symbol.name.clone(), Some("name")),
+            ]);
+        }
+        for word in self.words.iter() {
+            let properties = ((word.line_relocatable as u8) << 2)
+                + ((word.operand_relocatable as u8) << 1)
+                + word.operand_imported as u8;
+            let mut line = vec![
+                (
+                    format!("{properties:X}{:03X}", word.address),
+                    Some("address"),
+                ),
+                (" ".to_owned(), None),
+                (format!("{:X}", word.instruction), Some("instruction")),
+                (format!("{:03X}", word.operand), Some("operand")),
+            ];
+            if let Some(debug) = &word.debug {
+                line.push((" ; ! ".to_owned(), None));
+                let location = format!("{}:{}:{}", debug.file, debug.line, debug.column);
+                line.push((location, Some("debug")));
+                if let Some(label) = &debug.label {
+                    line.push((" ".to_owned(), None));
+                    line.push((label.clone(), Some("debug")));
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parser::Parse;
+    use crate::types::AddressedProgram;
+
+    const PROGRAM: &str = indoc! {"
+        2000 0000 ; > MAIN
+        2000 0004 ; >? TWO
+        1000 0000 ; < PRINT
+        6000 8004 ; ! MAIN.ASM:4:1 MAIN
+        5002 A000
+        4004 0002
+    "};
+
+    fn object() -> ObjectProgram {
+        let (_, program) = AddressedProgram::parse_machine_code(PROGRAM.into()).unwrap();
+        ObjectProgram::new(&program.lines)
+    }
+
+    #[test]
+    fn should_render_program() {
+        assert_eq!(object().to_string(), PROGRAM);
+    }
+
+    #[test]
+    fn should_round_trip_through_json() {
+        let object = object();
+        assert_eq!(ObjectProgram::from_json(&object.to_json()).unwrap(), object);
+    }
+
+    #[test]
+    fn should_read_json() {
+        let json = r#"{
+            "symbols": [{"kind": "import", "name": "PRINT", "value": 0}],
+            "words": [{"address": 0, "instruction": 10, "operand": 0, "operand_imported": true}]
+        }"#;
+        assert_eq!(
+            ObjectProgram::from_json(json).unwrap().to_string(),
+            "1000 0000 ; < PRINT\n1000 A000\n"
+        );
+    }
+
+    #[test]
+    fn text_should_point_back_to_json() {
+        let json = indoc! {r#"
+            {
+              "symbols": [{"kind": "import", "name": "PRINT", "value": 0}],
+              "words": [
+                {"address": 0, "instruction": 10, "operand": 0, "operand_imported": true},
+                {"address": 2, "instruction": 12, "operand": 0}
+              ]
+            }
+        "#};
+        let modern = ObjectProgram::from_json(json).unwrap().to_text(json);
+        assert_eq!(modern.text, "1000 0000 ; < PRINT\n1000 A000\n0002 C000\n");
+        // `PRINT` in the symbol table
+        let symbol = MvnReportError::new(Position::new(1, 15), None)
+            .with_span(SourceSpan::new(14, 19))
+            .locate(&modern.map, None);
+        assert_eq!(symbol.position, Position::new(2, 42));
+        assert_eq!(&json[symbol.span.start..symbol.span.end], "\"PRINT\"");
+        // Address of the last word
+        let address = MvnReportError::new(Position::new(3, 1), None)
+            .with_span(SourceSpan::new(30, 34))
+            .locate(&modern.map, None);
+        assert_eq!(address.position, Position::new(5, 17));
+        assert_eq!(&json[address.span.start..address.span.end], "2");
+    }
+
+    #[test]
+    fn values_too_large_should_fail() {
+        let json = indoc! {r#"
+            {
+              "symbols": [],
+              "words": [{"address": 0, "instruction": 12, "operand": 70000}]
+            }
+        "#};
+        let error = ObjectProgram::from_json(json).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::MalformedObject));
+        assert_eq!(error.position, Position::new(3, 58));
+        assert_eq!(&json[error.span.start..error.span.end], "70000");
+        let json =
+            r#"{"symbols": [], "words": [{"address": 0, "instruction": 200, "operand": 0}]}"#;
+        let error = ObjectProgram::from_json(json).unwrap_err();
+        assert_eq!(&json[error.span.start..error.span.end], "200");
+        let json =
+            r#"{"symbols": [{"kind": "export", "name": "MAIN", "value": 4096}], "words": []}"#;
+        let error = ObjectProgram::from_json(json).unwrap_err();
+        assert_eq!(&json[error.span.start..error.span.end], "4096");
+    }
+
+    #[test]
+    fn malformed_json_should_fail() {
+        let error = ObjectProgram::from_json("{\n  \"words\": 1\n}").unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::MalformedObject));
+        assert_eq!(error.position.line, 2);
+    }
+}
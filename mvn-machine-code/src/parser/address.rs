@@ -1,5 +1,7 @@
 use nom::bytes::complete::take;
+use utils::code::ErrorCode;
 use utils::hexadecimal;
+use utils::i18n::Message;
 
 use crate::types::{AddressPosition, MachineAddress, MachineAddressProperties};
 
@@ -8,7 +10,10 @@ use super::{Parse, Relocate};
 
 impl Relocate for MachineAddress {
     fn relocate(self, base: AddressPosition) -> Self {
-        // TODO Add error treatment
+        /* Addresses past the end of memory are reported by callers
+         * with `AddressedProgram::check_address_space`, which can tell
+         * which program and line caused them
+         */
         Self::new(self.properties, base + self.position)
     }
 }
@@ -27,10 +32,10 @@ impl Parse<'_> for MachineAddressProperties {
         let (_, nibble) = hexadecimal::<u8>(input)?;
         let properties = MachineAddressProperties::try_from(nibble);
         match properties {
-            Err(_) => Err(nom::Err::Error(error::MvnParseError::new(
-                "invalid address properties".to_owned(),
-                input,
-            ))),
+            Err(_) => Err(nom::Err::Error(
+                error::MvnParseError::new(Message::InvalidAddressProperties.to_string(), input)
+                    .with_code(ErrorCode::InvalidAddressProperties),
+            )),
             Ok(properties) => Ok(("".into(), properties)),
         }
     }
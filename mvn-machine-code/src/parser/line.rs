@@ -1,10 +1,11 @@
 use nom::character::complete::space1;
+use nom::combinator::map_opt;
 use nom::sequence::tuple;
 
 use assembly::parser::Parse as ParseAssembler;
 use utils::{comment_or_space, types::Token};
 
-use crate::types::{AddressPosition, AddressedLine, MachineAddress};
+use crate::types::{AddressPosition, AddressedLine, DebugRecord, LabelRecord, MachineAddress};
 
 use super::error;
 use super::{Parse, Relocate};
@@ -17,6 +18,7 @@ impl<'a> Parse<'a> for AddressedLine<'a> {
             assembly::types::Operation::parse_machine_code,
             comment_or_space,
         ))(input)?;
+        let debug = comment.and_then(|comment| DebugRecord::parse(comment.fragment()));
         let relational_annotation = match comment {
             Some(annotation) => {
                 let annotation = assembly::types::Line::parse_assembler(annotation);
@@ -30,13 +32,17 @@ impl<'a> Parse<'a> for AddressedLine<'a> {
             }
             _ => None,
         };
-        Ok((rest, Self::new(address, operation, relational_annotation)))
+        Ok((
+            rest,
+            Self::new(address, operation, relational_annotation).with_debug(debug),
+        ))
     }
 }
 
 impl Relocate for AddressedLine<'_> {
     fn relocate(self, base: AddressPosition) -> Self {
         let position = self.address.position;
+        let span = self.address.span;
         let address = self.address.value;
         let properties = address.properties;
         let address = if properties.line_relocatable {
@@ -52,10 +58,42 @@ impl Relocate for AddressedLine<'_> {
         };
 
         Self::new(
-            Token::new(position, address),
+            Token::new(position, address).with_span(span),
             operation,
             self.relational_annotation,
         )
+        .with_debug(self.debug)
+    }
+}
+
+// Only debug records naming a label make up a line of their own
+impl<'a> Parse<'a> for LabelRecord<'a> {
+    fn parse_machine_code(input: error::Span<'a>) -> error::LocatedIResult<'a, Self> {
+        map_opt(
+            tuple((
+                Token::<MachineAddress>::parse_machine_code,
+                comment_or_space,
+            )),
+            |(address, comment)| {
+                let debug = DebugRecord::parse(comment?.fragment())?;
+                debug.label.map(|_| Self::new(address, debug))
+            },
+        )(input)
+    }
+}
+
+impl Relocate for LabelRecord<'_> {
+    fn relocate(self, base: AddressPosition) -> Self {
+        let Token {
+            position,
+            span,
+            value: address,
+        } = self.address;
+        let address = match address.properties.line_relocatable {
+            true => address.relocate(base),
+            false => address,
+        };
+        Self::new(Token::new(position, address).with_span(span), self.debug)
     }
 }
 
@@ -136,6 +174,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn should_parse_label_records() {
+        let record = LabelRecord::parse_machine_code("4010 ; ! MAIN.ASM:3:1 BUFFER".into())
+            .unwrap()
+            .1;
+        assert_eq!(
+            record.address.value,
+            MachineAddress::new(MachineAddressProperties::new(true, false, false), 0x010)
+        );
+        assert_eq!(
+            record.debug,
+            DebugRecord::new("MAIN.ASM", 3, 1).with_label(Some("BUFFER".into()))
+        );
+        assert!(LabelRecord::parse_machine_code("4010 ; ! MAIN.ASM:3:1".into()).is_err());
+        assert!(LabelRecord::parse_machine_code("4010 8004 ; ! MAIN.ASM:3:1 X".into()).is_err());
+    }
+
     #[test]
     fn non_relational_comments_should_not_lead_to_relational_annotations() {
         let inputs = vec![
@@ -17,14 +17,14 @@ pub trait Relocate: Sized {
 }
 
 use nom_locate::position;
-use utils::types::Token;
+use utils::types::{SourceSpan, Token};
 
 impl<'a, T: Parse<'a>> Parse<'a> for Token<T> {
     fn parse_machine_code(input: error::Span<'a>) -> error::LocatedIResult<'a, Self> {
         let (input, position) = position(input)?;
         let position = position.into();
         let (rest, value) = T::parse_machine_code(input)?;
-        let token = Token { position, value };
+        let token = Token::new(position, value).with_span(SourceSpan::between(input, rest));
         Ok((rest, token))
     }
 }
@@ -14,13 +14,15 @@ impl<'a> Parse<'a> for Operation<'a> {
         let (instruction, instruction_position) = position(instruction)?;
         let instruction_position = Position::from(instruction_position);
         let (_, instruction) = Instruction::parse_machine_code(instruction)?;
-        let instruction = Token::new(instruction_position, instruction);
+        let instruction = Token::new(instruction_position, instruction)
+            .with_span(SourceSpan::between(input, operand));
 
         let (operand, operand_position) = position(operand)?;
         let operand_position = Position::from(operand_position);
-        let (rest, operand) = hexadecimal::<u32>(operand)?;
-        let operand = Operand::new_numeric(operand);
-        let operand = Token::new(operand_position, operand);
+        let (rest, operand_value) = hexadecimal::<u32>(operand)?;
+        let operand_value = Operand::new_numeric(operand_value);
+        let operand = Token::new(operand_position, operand_value)
+            .with_span(SourceSpan::between(operand, rest));
 
         Ok((rest, Self::new(instruction, operand)))
     }
@@ -37,7 +39,7 @@ impl Relocate for Operation<'_> {
 
         Self::new(
             self.instruction,
-            Token::new(self.operand.position, (base + operand).into()),
+            Token::new(self.operand.position, (base + operand).into()).with_span(self.operand.span),
         )
     }
 }
@@ -72,4 +74,11 @@ mod tests {
     fn should_reject_invalid_instruction() {
         assert!(Operation::parse_machine_code("G000".into()).is_err());
     }
+
+    #[test]
+    fn should_locate_instruction_and_operand() {
+        let (_, operation) = Operation::parse_machine_code("8002".into()).unwrap();
+        assert_eq!(operation.instruction.span, SourceSpan::new(0, 1));
+        assert_eq!(operation.operand.span, SourceSpan::new(1, 4));
+    }
 }
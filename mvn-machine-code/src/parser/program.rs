@@ -1,8 +1,10 @@
+use nom::branch::alt;
 use nom::character::complete::line_ending;
 use nom::combinator::map;
-use nom::multi::separated_list1;
+use nom::multi::{many0, many1, separated_list1};
+use nom::sequence::preceded;
 
-use crate::types::{AddressedLine, AddressedProgram};
+use crate::types::{AddressedLine, AddressedProgram, LabelRecord};
 
 use super::error;
 use super::{AddressPosition, Parse, Relocate};
@@ -15,15 +17,42 @@ impl Relocate for AddressedProgram<'_> {
                 .map(|line| line.relocate(base))
                 .collect(),
         )
+        .with_labels(
+            self.labels
+                .into_iter()
+                .map(|label| label.relocate(base))
+                .collect(),
+        )
     }
 }
 
+// Lines either place a word or only tell where a label is
+enum Entry<'a> {
+    Word(Box<AddressedLine<'a>>),
+    Label(LabelRecord<'a>),
+}
+
 impl<'a> Parse<'a> for AddressedProgram<'a> {
     fn parse_machine_code(input: error::Span<'a>) -> error::LocatedIResult<'a, Self> {
-        map(
-            separated_list1(line_ending, AddressedLine::parse_machine_code),
-            Self::new,
-        )(input)
+        let entry = alt((
+            map(LabelRecord::parse_machine_code, Entry::Label),
+            map(AddressedLine::parse_machine_code, |line| {
+                Entry::Word(Box::new(line))
+            }),
+        ));
+        // Blank lines are skipped, so that rewritten programs keep their numbering
+        let (rest, entries) = preceded(
+            many0(line_ending),
+            separated_list1(many1(line_ending), entry),
+        )(input)?;
+        let mut program = Self::new(Vec::new());
+        for entry in entries {
+            match entry {
+                Entry::Word(line) => program.lines.push(*line),
+                Entry::Label(label) => program.labels.push(label),
+            }
+        }
+        Ok((rest, program))
     }
 }
 
@@ -53,9 +82,7 @@ mod tests {
         let program = AddressedProgram::parse_machine_code(program.as_str().into())
             .unwrap()
             .1;
-        for (i, (source_line, parsed_line)) in
-            lines.into_iter().zip(program.into_iter()).enumerate()
-        {
+        for (i, (source_line, parsed_line)) in lines.into_iter().zip(program).enumerate() {
             let i = (i + 1).try_into().unwrap();
             let mut addressed_line = AddressedLine::parse_machine_code(source_line.into())
                 .unwrap()
@@ -85,4 +112,25 @@ mod tests {
             assert_eq!(addressed_line, parsed_line,);
         }
     }
+
+    #[test]
+    fn should_skip_blank_lines() {
+        let program = "\n0000 3012\n\n\n0002 5000\n";
+        let (_, program) = AddressedProgram::parse_machine_code(program.into()).unwrap();
+        let lines: Vec<u32> = program
+            .lines
+            .iter()
+            .map(|line| line.address.position.line)
+            .collect();
+        assert_eq!(lines, vec![2, 5]);
+    }
+
+    #[test]
+    fn should_set_label_records_apart() {
+        let program = "4000 8004 ; ! MAIN.ASM:1:1 MAIN\n4004 ; ! MAIN.ASM:3:1 BUFFER\n";
+        let (_, program) = AddressedProgram::parse_machine_code(program.into()).unwrap();
+        assert_eq!(program.lines.len(), 1);
+        let labels: Vec<String> = program.labels.iter().map(ToString::to_string).collect();
+        assert_eq!(labels, vec!["4004 ; ! MAIN.ASM:3:1 BUFFER"]);
+    }
 }
@@ -0,0 +1,131 @@
+use std::fmt;
+
+use utils::types::Token;
+
+use super::{Label, MachineAddress};
+
+/// Place in the assembly source a word came from, along with the label
+/// defined on its line, written after the word as `; ! FILE:LINE:COLUMN LABEL`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugRecord<'a> {
+    pub file: &'a str,
+    pub line: u32,
+    pub column: usize,
+    pub label: Option<Label<'a>>,
+}
+
+impl<'a> DebugRecord<'a> {
+    pub fn new(file: &'a str, line: u32, column: usize) -> Self {
+        Self {
+            file,
+            line,
+            column,
+            label: None,
+        }
+    }
+
+    pub fn with_label(self, label: Option<Label<'a>>) -> Self {
+        Self { label, ..self }
+    }
+
+    /// Record in a comment, if the comment holds one
+    pub fn parse(comment: &'a str) -> Option<Self> {
+        let mut fields = comment.strip_prefix('!')?.split_whitespace();
+        let mut location = fields.next()?.rsplitn(3, ':');
+        let column = location.next()?.parse().ok()?;
+        let line = location.next()?.parse().ok()?;
+        let file = location.next()?;
+        let label = fields.next().map(Label::from);
+        Some(Self::new(file, line, column).with_label(label))
+    }
+}
+
+/// Label defined on a line that places no word, such as one reserving
+/// memory, written on a line of its own as `ADDRESS ; ! FILE:LINE:COLUMN LABEL`
+#[derive(Debug, PartialEq)]
+pub struct LabelRecord<'a> {
+    pub address: Token<MachineAddress>,
+    pub debug: DebugRecord<'a>,
+}
+
+impl<'a> LabelRecord<'a> {
+    pub fn new(address: Token<MachineAddress>, debug: DebugRecord<'a>) -> Self {
+        Self { address, debug }
+    }
+}
+
+impl fmt::Display for LabelRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:X} ; {}", self.address, self.debug)
+    }
+}
+
+/// Program in uppercase, as programs are read, except for the files named
+/// by its debug records, which may be told apart by their case
+pub fn uppercase_program(program: &str) -> String {
+    program
+        .split_inclusive('\n')
+        .map(|line| {
+            let record = line
+                .split_once(';')
+                .and_then(|(_, comment)| comment.trim_start().strip_prefix('!'))
+                .map(str::trim_start);
+            let Some(record) = record else {
+                return line.to_uppercase();
+            };
+            let start = line.len() - record.len();
+            let end = start + record.find(char::is_whitespace).unwrap_or(record.len());
+            format!(
+                "{}{}{}",
+                line[..start].to_uppercase(),
+                &line[start..end],
+                line[end..].to_uppercase()
+            )
+        })
+        .collect()
+}
+
+impl fmt::Display for DebugRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "! {}:{}:{}", self.file, self.line, self.column)?;
+        match &self.label {
+            Some(label) => write!(f, " {label}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_debug_record() {
+        assert_eq!(
+            DebugRecord::parse("! MAIN.ASM:3:1 LOOP"),
+            Some(DebugRecord::new("MAIN.ASM", 3, 1).with_label(Some(Label::from("LOOP"))))
+        );
+        assert_eq!(
+            DebugRecord::parse("! C:/MAIN.ASM:12:9"),
+            Some(DebugRecord::new("C:/MAIN.ASM", 12, 9))
+        );
+        assert_eq!(DebugRecord::parse("> LOOP"), None);
+        assert_eq!(DebugRecord::parse("! MAIN.ASM"), None);
+    }
+
+    #[test]
+    fn should_keep_case_of_debug_record_files() {
+        assert_eq!(
+            uppercase_program("4000 8004 ; ! src/Main.asm:2:1 loop\n0002 c000 ; > main\n"),
+            "4000 8004 ; ! src/Main.asm:2:1 LOOP\n0002 C000 ; > MAIN\n"
+        );
+    }
+
+    #[test]
+    fn should_display_debug_record() {
+        let record = DebugRecord::new("MAIN.ASM", 3, 1).with_label(Some(Label::from("LOOP")));
+        assert_eq!(record.to_string(), "! MAIN.ASM:3:1 LOOP");
+    }
+}
@@ -2,15 +2,16 @@ use std::fmt;
 
 use utils::types::Token;
 
-use crate::types::{AddressPosition, Operation};
+use crate::types::{AddressPosition, Instruction, Operand, Operation};
 
-use super::MachineAddress;
+use super::{DebugRecord, MachineAddress};
 
 #[derive(Debug, PartialEq)]
 pub struct AddressedLine<'a> {
     pub address: Token<MachineAddress>,
     pub operation: Operation<'a>,
     pub relational_annotation: Option<assembly::types::Line<'a>>,
+    pub debug: Option<DebugRecord<'a>>,
 }
 
 impl<'a> AddressedLine<'a> {
@@ -23,9 +24,27 @@ impl<'a> AddressedLine<'a> {
             address,
             operation,
             relational_annotation,
+            debug: None,
         }
     }
 
+    pub fn with_debug(self, debug: Option<DebugRecord<'a>>) -> Self {
+        Self { debug, ..self }
+    }
+
+    /// Word placed in memory by the line, with the instruction in its top nibble
+    pub fn word(&self) -> u16 {
+        let instruction = match self.operation.instruction.value {
+            Instruction::Normal(mneumonic) => u8::from(mneumonic),
+            _ => 0,
+        };
+        let operand = match self.operation.operand.value {
+            Operand::Numeric(operand) => operand,
+            Operand::Symbolic(_) => 0,
+        };
+        ((u32::from(instruction) << 12) | operand) as u16
+    }
+
     // FIXME Modify API to get rid of this method
     pub fn destruct(self) -> (assembly::types::Label<'a>, AddressPosition, bool) {
         let annotation = self.relational_annotation.unwrap();
@@ -45,6 +64,8 @@ impl fmt::Display for AddressedLine<'_> {
         write!(f, "{:X} {:X}{:}", self.address, instruction, operand)?;
         if let Some(annotation) = &self.relational_annotation {
             write!(f, " ; {annotation}")
+        } else if let Some(debug) = &self.debug {
+            write!(f, " ; {debug}")
         } else {
             Ok(())
         }
@@ -1,12 +1,17 @@
 mod address;
+mod debug;
 mod line;
 mod program;
 
 // TODO Reference utils::types::AddressPosition
 pub type AddressPosition = u32;
 
+// Last address of the 4 KiB memory of the MVN
+pub const MEMORY_END: AddressPosition = 0xFFF;
+
 pub use address::{MachineAddress, MachineAddressProperties};
+pub use debug::{uppercase_program, DebugRecord, LabelRecord};
 pub use line::AddressedLine;
-pub use program::AddressedProgram;
+pub use program::{first_free_base, first_overlap, AddressedProgram};
 
 pub use assembly::types::{mneumonic, Instruction, Label, Operand, Operation};
@@ -1,16 +1,32 @@
+use utils::code::ErrorCode;
+use utils::error::MvnReportError;
+use utils::i18n::Message;
+
 use super::line::AddressedLine;
-use super::{mneumonic::RelationalMneumonic, AddressPosition, Instruction};
+use super::{
+    mneumonic::RelationalMneumonic, AddressPosition, Instruction, Label, LabelRecord, Operand,
+    MEMORY_END,
+};
 
 type Lines<'a> = Vec<AddressedLine<'a>>;
 
 #[derive(Debug)]
 pub struct AddressedProgram<'a> {
     pub lines: Lines<'a>,
+    // Only kept by steps writing our own dialect, as they place no word
+    pub labels: Vec<LabelRecord<'a>>,
 }
 
 impl<'a> AddressedProgram<'a> {
     pub fn new(lines: Lines<'a>) -> Self {
-        Self { lines }
+        Self {
+            lines,
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn with_labels(self, labels: Vec<LabelRecord<'a>>) -> Self {
+        Self { labels, ..self }
     }
 
     // TODO Implement wrapper types for imports, exports and instructions
@@ -32,6 +48,68 @@ impl<'a> AddressedProgram<'a> {
         (imports, exports, instructions)
     }
 
+    /// Labels of the symbol table entries declared with `mneumonic`
+    pub fn symbols(&self, mneumonic: RelationalMneumonic) -> Vec<Label<'a>> {
+        self.lines
+            .iter()
+            .filter_map(|line| line.relational_annotation.as_ref())
+            .filter(|line| line.operation.instruction.value == Instruction::Relational(mneumonic))
+            .filter_map(|line| match &line.operation.operand.value {
+                Operand::Symbolic(label) => Some(*label),
+                Operand::Numeric(_) => None,
+            })
+            .collect()
+    }
+
+    /// Space taken by relocatable instructions, which start at 0 until relocated
+    pub fn relocatable_size(&self) -> AddressPosition {
+        self.lines
+            .iter()
+            .filter(|line| line.relational_annotation.is_none())
+            .filter(|line| line.address.value.properties.line_relocatable)
+            .map(|line| line.address.value.position + 0x2)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Addresses taken by absolute instructions, which stay where they were
+    /// assembled, as ranges with exclusive ends
+    pub fn absolute_ranges(&self) -> impl Iterator<Item = (AddressPosition, AddressPosition)> + '_ {
+        self.lines
+            .iter()
+            .filter(|line| line.relational_annotation.is_none())
+            .filter(|line| !line.address.value.properties.line_relocatable)
+            .map(|line| line.address.value.position)
+            .map(|position| (position, position + 0x2))
+    }
+
+    /// Fails on the first line placed past the end of memory or, having a
+    /// relocatable operand, pointing past it
+    pub fn check_address_space(&self, code: ErrorCode) -> Result<(), MvnReportError> {
+        for line in self.lines.iter() {
+            let position = line.address.value.position;
+            if position > MEMORY_END {
+                return Err(MvnReportError::new(
+                    line.address.position,
+                    Some(Message::WordOutsideMemory(position).to_string()),
+                )
+                .with_code(code)
+                .with_span(line.address.span));
+            }
+            if let Operand::Numeric(operand) = line.operation.operand.value {
+                if line.address.value.properties.operand_relocatable && operand > MEMORY_END {
+                    return Err(MvnReportError::new(
+                        line.operation.operand.position,
+                        Some(Message::OperandOutsideMemory(operand).to_string()),
+                    )
+                    .with_code(code)
+                    .with_span(line.operation.operand.span));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_last_position(&self) -> AddressPosition {
         self.lines
             .iter()
@@ -40,6 +118,36 @@ impl<'a> AddressedProgram<'a> {
     }
 }
 
+/// First two words sharing an address, each word taking two, given the
+/// lines they were placed by along with whatever identifies their programs
+pub fn first_overlap<'l, 'a, T: Copy>(
+    lines: impl IntoIterator<Item = (T, &'l AddressedLine<'a>)>,
+) -> Option<[(T, &'l AddressedLine<'a>); 2]> {
+    let mut words: Vec<(T, &AddressedLine)> = lines.into_iter().collect();
+    words.sort_by_key(|(_, line)| line.address.value.position);
+    words
+        .windows(2)
+        .find(|pair| pair[1].1.address.value.position - pair[0].1.address.value.position < 0x2)
+        .map(|pair| [pair[0], pair[1]])
+}
+
+/// Lowest even base from `from` on at which `size` addresses don't
+/// intersect any of the `taken` ranges, which may run past the end of memory
+pub fn first_free_base(
+    taken: &[(AddressPosition, AddressPosition)],
+    from: AddressPosition,
+    size: AddressPosition,
+) -> AddressPosition {
+    let mut base = from;
+    while let Some((_, end)) = taken
+        .iter()
+        .find(|(start, end)| size > 0 && *start < base + size && base < *end)
+    {
+        base = end.next_multiple_of(0x2);
+    }
+    base
+}
+
 impl<'a> IntoIterator for AddressedProgram<'a> {
     type Item = AddressedLine<'a>;
     type IntoIter = std::vec::IntoIter<Self::Item>;
@@ -142,8 +250,71 @@ mod tests {
         assert_eq!(instructions, test_instructions());
     }
 
+    #[test]
+    fn should_list_symbols() {
+        let program = test_program();
+        assert_eq!(
+            program.symbols(RelationalMneumonic::Export),
+            vec![Label::from("FOO")]
+        );
+        assert_eq!(
+            program.symbols(RelationalMneumonic::Import),
+            vec![Label::from("BAR")]
+        );
+    }
+
     #[test]
     fn should_get_last_position() {
         assert_eq!(test_program().get_last_position(), 0x100);
     }
+
+    #[test]
+    fn should_check_address_space() {
+        let code = ErrorCode::ProgramOutsideMemory;
+        assert!(test_program().check_address_space(code).is_ok());
+
+        let mut lines = test_instructions();
+        lines[1].address.value.position = 0x1000;
+        let error = AddressedProgram::new(lines)
+            .check_address_space(code)
+            .unwrap_err();
+        assert_eq!(error.code, Some(code));
+
+        let mut lines = test_instructions();
+        lines[0].address.value.properties.operand_relocatable = true;
+        lines[0].operation.operand.value = Operand::new_numeric(0x1000);
+        assert!(AddressedProgram::new(lines)
+            .check_address_space(code)
+            .is_err());
+    }
+
+    #[test]
+    fn should_find_overlapping_words() {
+        let program = test_program();
+        assert_eq!(
+            first_overlap(program.lines.iter().map(|line| ((), line))),
+            None
+        );
+
+        let mut lines = test_instructions();
+        lines[1].address.value.position = 0x005;
+        let overlap = first_overlap(lines.iter().enumerate()).unwrap();
+        assert_eq!(overlap.map(|(i, _)| i), [0, 1]);
+    }
+
+    #[test]
+    fn should_find_first_free_base() {
+        let taken = [(0x000, 0x002), (0x010, 0x013)];
+        assert_eq!(first_free_base(&taken, 0x000, 0x004), 0x002);
+        assert_eq!(first_free_base(&taken, 0x000, 0x010), 0x014);
+        assert_eq!(first_free_base(&taken, 0x000, 0x000), 0x000);
+    }
+
+    #[test]
+    fn should_get_relocatable_size() {
+        assert_eq!(test_program().relocatable_size(), 0);
+        let mut lines = test_instructions();
+        lines[0].address.value.properties.line_relocatable = true;
+        assert_eq!(AddressedProgram::new(lines).relocatable_size(), 0x006);
+    }
 }
@@ -2,3 +2,123 @@ pub mod processor;
 pub mod writer;
 
 pub use machine_code::*;
+
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Parser;
+use utils::i18n::{Lang, Message};
+use utils::io::{file_exists, read_input_verbatim, Source};
+use utils::report::MessageFormat;
+use utils::Executor;
+
+use crate::processor::{process, Range, Segment};
+use crate::types::{uppercase_program, AddressPosition, MEMORY_END};
+use crate::writer::{print, render_symbols, ImageFormat};
+
+#[derive(Parser)]
+#[command(author, version, about = Message::HelpRelocate.to_string(), long_about = None)]
+pub struct Args {
+    #[arg(short, long, value_parser = file_exists, help = Message::HelpInputOrStdin.to_string())]
+    pub input: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        value_parser = clap_num::maybe_hex::<u16>,
+        help = Message::HelpBase.to_string()
+    )]
+    pub base: Option<u16>,
+    #[arg(long, value_parser = clap_num::maybe_hex::<u16>, help = Message::HelpData.to_string())]
+    pub data: Option<u16>,
+    #[arg(
+        long,
+        requires = "data",
+        value_parser = clap_num::maybe_hex::<u16>,
+        help = Message::HelpDataBase.to_string()
+    )]
+    pub data_base: Option<u16>,
+    #[arg(
+        long = "reserve",
+        value_name = "START-END",
+        value_parser = parse_range,
+        help = Message::HelpReserve.to_string()
+    )]
+    pub reserved: Vec<Range>,
+    #[arg(long, help = Message::HelpKeepExports.to_string())]
+    pub keep_exports: bool,
+    #[arg(long, value_name = "FILE", help = Message::HelpSymbols.to_string())]
+    pub symbols: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t, help = Message::HelpFormat.to_string())]
+    pub format: ImageFormat,
+    #[arg(
+        long,
+        default_value_t,
+        value_parser = clap_num::maybe_hex::<u8>,
+        help = Message::HelpFill.to_string()
+    )]
+    pub fill: u8,
+    #[arg(long, value_enum, default_value_t, help = Message::HelpMessageFormat.to_string())]
+    pub message_format: MessageFormat,
+    // Read before parsing by `Lang::detect`, so that help is translated as well
+    #[arg(long, value_enum, help = Message::HelpLang.to_string())]
+    pub lang: Option<Lang>,
+}
+
+impl Executor for Args {
+    fn execute(&self) {
+        let (name, content) = read_input_verbatim(self.input.as_ref());
+        let content = uppercase_program(&content);
+        let process_result =
+            process(&content, &self.segments(), &self.reserved).map(|(program, bases)| {
+                self.report_bases(&bases);
+                program
+            });
+        if let (Some(path), Ok(program)) = (&self.symbols, &process_result) {
+            fs::write(path, render_symbols(program)).expect("failed to write file");
+        }
+        print(
+            Source::new(&name, &content),
+            process_result,
+            self.keep_exports,
+            self.format,
+            self.fill,
+            self.message_format,
+        );
+    }
+}
+
+impl Args {
+    fn segments(&self) -> Vec<Segment> {
+        let base = self.base.map(AddressPosition::from);
+        let mut segments = vec![Segment::new(0, base)];
+        if let Some(data) = self.data {
+            let data_base = self.data_base.map(AddressPosition::from);
+            segments.push(Segment::new(data.into(), data_base));
+        }
+        segments
+    }
+
+    // Bases are only worth mentioning when they weren't all given
+    fn report_bases(&self, bases: &[AddressPosition]) {
+        let given = self.base.is_some() && (self.data.is_none() || self.data_base.is_some());
+        if given || self.message_format != MessageFormat::Human {
+            return;
+        }
+        eprintln!("{}", Message::CodeRelocatedTo(bases[0]));
+        if let Some(base) = bases.get(1) {
+            eprintln!("{}", Message::DataRelocatedTo(*base));
+        }
+    }
+}
+
+fn parse_range(range: &str) -> Result<Range, String> {
+    let error = || Message::InvalidReservedRange(range).to_string();
+    let (start, end) = range.split_once('-').ok_or_else(error)?;
+    let start = clap_num::maybe_hex::<u16>(start).map_err(|_| error())?;
+    let end = clap_num::maybe_hex::<u16>(end).map_err(|_| error())?;
+    let (start, end) = (AddressPosition::from(start), AddressPosition::from(end));
+    if start > end || end > MEMORY_END {
+        return Err(error());
+    }
+    Ok((start, end + 1))
+}
@@ -1,33 +1,368 @@
-use machine_code::parser::Relocate;
-use utils::error::MvnReportError;
+use utils::code::ErrorCode;
+use utils::error::{MvnReportError, MvnReportLabel};
+use utils::i18n::Message;
 
 use crate::parser::Parse;
-use crate::types::{AddressedLine, AddressedProgram};
+use crate::types::{
+    first_free_base, first_overlap, AddressPosition, AddressedLine, AddressedProgram, LabelRecord,
+    Operand, MEMORY_END,
+};
 
-pub fn process(program: &str, relocation_base: u16) -> Result<AddressedProgram, MvnReportError> {
-    let instructions = parse(program)?;
-    Ok(instructions
+/// Relocatable code from `start` up to the start of the next segment, moved
+/// to `base` or, without one, to the lowest base left free
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    pub start: AddressPosition,
+    pub base: Option<AddressPosition>,
+}
+
+impl Segment {
+    pub fn new(start: AddressPosition, base: Option<AddressPosition>) -> Self {
+        Self { start, base }
+    }
+}
+
+/// Addresses from the first up to, but not including, the second
+pub type Range = (AddressPosition, AddressPosition);
+
+/*
+ * Segments must be sorted by their start, the first one starting at 0.
+ * Along with the relocated program, returns the base of each segment
+ */
+pub fn process<'a>(
+    program: &'a str,
+    segments: &[Segment],
+    reserved: &[Range],
+) -> Result<(AddressedProgram<'a>, Vec<AddressPosition>), MvnReportError> {
+    let (exports, instructions, labels) = parse(program)?;
+    let instructions = AddressedProgram::new(instructions);
+    let bases = allocate_bases(&instructions, segments, reserved)?;
+    let relocation: Vec<Range> = segments
+        .iter()
+        .zip(&bases)
+        .map(|(segment, base)| (segment.start, *base))
+        .collect();
+    let instructions = relocate(instructions.lines, &relocation);
+    let exports = relocate(exports, &relocation);
+    instructions.check_address_space(ErrorCode::RelocatedOutsideMemory)?;
+    exports.check_address_space(ErrorCode::RelocatedOutsideMemory)?;
+    // Exports sit at address 0 and only point at instructions, so they can't overlap them
+    check_overlaps(&instructions)?;
+    let program: AddressedProgram = instructions
         .into_iter()
-        .map(|line| {
-            let mut line = line.relocate(relocation_base as u32);
+        .chain(exports)
+        .map(|mut line| {
             line.address.value.properties = Default::default();
             line
         })
-        .collect())
+        .collect();
+    let labels = labels
+        .into_iter()
+        .map(|mut label| {
+            let address = &mut label.address.value;
+            if address.properties.line_relocatable {
+                address.position = relocate_position(address.position, &relocation);
+            }
+            address.properties = Default::default();
+            label
+        })
+        .collect();
+    Ok((program.with_labels(labels), bases))
+}
+
+/*
+ * Segments with a given base are placed first, so that the others fit
+ * around them, absolute code and the reserved ranges
+ */
+fn allocate_bases(
+    program: &AddressedProgram,
+    segments: &[Segment],
+    reserved: &[Range],
+) -> Result<Vec<AddressPosition>, MvnReportError> {
+    let lines_of = |index: usize| {
+        let start = segments[index].start;
+        let end = segments
+            .get(index + 1)
+            .map_or(AddressPosition::MAX, |s| s.start);
+        program
+            .lines
+            .iter()
+            .filter(|line| line.relational_annotation.is_none())
+            .filter(|line| line.address.value.properties.line_relocatable)
+            .filter(move |line| (start..end).contains(&line.address.value.position))
+    };
+    // Addresses taken by each segment, relative to its base
+    let extents: Vec<Range> = (0..segments.len())
+        .map(|index| {
+            let positions = lines_of(index).map(|line| line.address.value.position);
+            let start = segments[index].start;
+            match (positions.clone().min(), positions.max()) {
+                (Some(first), Some(last)) => (first - start, last + 0x2 - start),
+                _ => (0, 0),
+            }
+        })
+        .collect();
+
+    let mut taken: Vec<Range> = reserved.to_vec();
+    taken.extend(program.absolute_ranges());
+    for (segment, (first, end)) in segments.iter().zip(&extents) {
+        if let Some(base) = segment.base {
+            taken.push((base + first, base + end));
+        }
+    }
+
+    let mut bases = Vec::new();
+    for (index, (segment, (first, end))) in segments.iter().zip(&extents).enumerate() {
+        let base = match segment.base {
+            Some(base) => base,
+            None => {
+                let size = end - first;
+                let base = first_free_base(&taken, *first, size) - first;
+                if base + end > MEMORY_END + 1 {
+                    let line = lines_of(index).next().unwrap_or(&program.lines[0]);
+                    return Err(MvnReportError::new(
+                        line.address.position,
+                        Some(Message::NoRoomForProgram(size).to_string()),
+                    )
+                    .with_code(ErrorCode::NoRoomForSegment)
+                    .with_span(line.address.span));
+                }
+                taken.push((base + first, base + end));
+                base
+            }
+        };
+        bases.push(base);
+    }
+
+    // Chosen bases already avoid reserved ranges, but given ones may fall in them
+    for (index, (base, (first, end))) in bases.iter().zip(&extents).enumerate() {
+        let overlap = reserved.iter().find(|(start, reserved_end)| {
+            first < end && base + first < *reserved_end && *start < base + end
+        });
+        if let Some((start, reserved_end)) = overlap {
+            let line = lines_of(index).next().unwrap_or(&program.lines[0]);
+            return Err(MvnReportError::new(
+                line.address.position,
+                Some(Message::SegmentInReservedRange(*base, *start, reserved_end - 1).to_string()),
+            )
+            .with_code(ErrorCode::SegmentInReservedRange)
+            .with_span(line.address.span));
+        }
+    }
+    Ok(bases)
+}
+
+// Each relocatable address is moved along with the segment it falls in
+fn relocate_position(position: AddressPosition, relocation: &[Range]) -> AddressPosition {
+    match relocation
+        .iter()
+        .rev()
+        .find(|(start, _)| *start <= position)
+    {
+        Some((start, base)) => base + position - start,
+        None => position,
+    }
+}
+
+fn relocate<'a>(lines: Lines<'a>, relocation: &[Range]) -> AddressedProgram<'a> {
+    let relocate = |position: AddressPosition| relocate_position(position, relocation);
+    lines
+        .into_iter()
+        .map(|mut line| {
+            let properties = line.address.value.properties;
+            if properties.line_relocatable {
+                line.address.value.position = relocate(line.address.value.position);
+            }
+            if let (true, Operand::Numeric(operand)) = (
+                properties.operand_relocatable,
+                &line.operation.operand.value,
+            ) {
+                line.operation.operand.value = Operand::Numeric(relocate(*operand));
+            }
+            line
+        })
+        .collect()
 }
 
-fn parse(program: &str) -> Result<Vec<AddressedLine>, MvnReportError> {
+fn check_overlaps(program: &AddressedProgram) -> Result<(), MvnReportError> {
+    match first_overlap(program.lines.iter().map(|line| ((), line))) {
+        Some([(_, other_line), (_, line)]) => {
+            let label = MvnReportLabel::new(
+                None,
+                other_line.address.position,
+                Message::OtherWordPlacedHere.to_string(),
+            )
+            .with_span(other_line.address.span);
+            Err(MvnReportError::new(
+                line.address.position,
+                Some(Message::OverlappingWords(line.address.value.position).to_string()),
+            )
+            .with_code(ErrorCode::OverlappingRelocatedWords)
+            .with_span(line.address.span)
+            .with_label(label))
+        }
+        None => Ok(()),
+    }
+}
+
+type Lines<'a> = Vec<AddressedLine<'a>>;
+
+// Exports, instructions and labels placing no word of a linked program
+fn parse(program: &str) -> Result<(Lines<'_>, Lines<'_>, Vec<LabelRecord<'_>>), MvnReportError> {
     let parse_result = AddressedProgram::parse_machine_code(program.into());
-    let (_, program) = parse_result.map_err(|e| match e {
+    let (_, mut program) = parse_result.map_err(|e| match e {
         nom::Err::Error(e) | nom::Err::Failure(e) => MvnReportError::from(e),
         nom::Err::Incomplete(e) => panic!("Unhandled error `{e:?}` occured"),
     })?;
-    let (symbol_table, instructions): (Vec<AddressedLine>, Vec<AddressedLine>) = program
-        .lines
-        .into_iter()
-        .partition(|line| line.relational_annotation.is_some());
-    if symbol_table.first().is_some() {
-        panic!("symbol table present")
+    let labels = std::mem::take(&mut program.labels);
+    let (imports, exports, instructions) = program.partition();
+    match imports.split_first() {
+        Some((first, others)) => Err(unresolved_imports_error(first, others)),
+        None => Ok((exports, instructions, labels)),
+    }
+}
+
+fn unresolved_imports_error(first: &AddressedLine, others: &[AddressedLine]) -> MvnReportError {
+    let symbol = |line: &AddressedLine| match line.relational_annotation.as_ref() {
+        Some(annotation) => annotation.operation.operand.value.to_string(),
+        None => String::new(),
+    };
+    let symbols: Vec<String> = std::iter::once(first).chain(others).map(symbol).collect();
+    let error = MvnReportError::new(
+        first.address.position,
+        Some(Message::ImportsOnRelocation(&symbols.join(", ")).to_string()),
+    )
+    .with_code(ErrorCode::ImportsOnRelocation)
+    .with_span(first.address.span)
+    .with_help(Message::LinkBeforeRelocating.to_string());
+    others.iter().fold(error, |error, line| {
+        error.with_label(
+            MvnReportLabel::new(
+                None,
+                line.address.position,
+                Message::SymbolImportedHere(&symbol(line)).to_string(),
+            )
+            .with_span(line.address.span),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::writer::render_symbols;
+
+    #[test]
+    fn exports_should_be_relocated() {
+        let program = indoc! {"
+            4000 0000
+            6002 4000
+            2000 0002 ; > PRINT
+            0000 0008 ; > ABSOLUTE
+        "};
+        let segments = [Segment::new(0, Some(0x100))];
+        let (program, _) = process(program, &segments, &[]).unwrap();
+        let lines: Vec<String> = program.lines.iter().map(ToString::to_string).collect();
+        assert_eq!(lines[..2], ["0100 0000", "0102 4100"]);
+        assert_eq!(render_symbols(&program), "PRINT /102\nABSOLUTE /008\n");
+    }
+
+    #[test]
+    fn lowest_free_base_should_avoid_reserved_and_absolute_code() {
+        let program = indoc! {"
+            4000 0002
+            6002 8000
+            0102 0000
+        "};
+        let segments = [Segment::new(0, None)];
+        let (program, bases) = process(program, &segments, &[(0x000, 0x100)]).unwrap();
+        assert_eq!(bases, [0x104]);
+        assert_eq!(program.lines[1].to_string(), "0106 8104");
+    }
+
+    #[test]
+    fn lowest_free_base_should_account_for_where_code_starts() {
+        // Relocatable code was linked after the absolute code, so it may stay where it is
+        let program = indoc! {"
+            0000 3001
+            0002 C000
+            400C 3002
+            600E 800C
+        "};
+        let segments = [Segment::new(0, None)];
+        let (program, bases) = process(program, &segments, &[]).unwrap();
+        assert_eq!(bases, [0x000]);
+        assert_eq!(program.lines[3].to_string(), "000E 800C");
+    }
+
+    #[test]
+    fn segments_should_be_relocated_to_their_bases() {
+        let program = indoc! {"
+            6000 8004
+            4002 C000
+            4004 0001
+        "};
+        let segments = [Segment::new(0, Some(0x100)), Segment::new(0x004, None)];
+        let (program, bases) = process(program, &segments, &[]).unwrap();
+        assert_eq!(bases, [0x100, 0x000]);
+        let lines: Vec<String> = program.lines.iter().map(ToString::to_string).collect();
+        assert_eq!(lines, ["0100 8000", "0102 C000", "0000 0001"]);
+    }
+
+    #[test]
+    fn label_records_should_follow_their_segment() {
+        let program = indoc! {"
+            6000 8004 ; ! MAIN.ASM:1:1 MAIN
+            4002 C000 ; ! MAIN.ASM:2:9
+            4004 ; ! MAIN.ASM:3:1 BUFFER
+        "};
+        let segments = [
+            Segment::new(0, Some(0x100)),
+            Segment::new(0x004, Some(0x200)),
+        ];
+        let (program, _) = process(program, &segments, &[]).unwrap();
+        let labels: Vec<String> = program.labels.iter().map(ToString::to_string).collect();
+        assert_eq!(labels, ["0200 ; ! MAIN.ASM:3:1 BUFFER"]);
+    }
+
+    #[test]
+    fn segment_without_room_should_fail() {
+        let program = "4000 0000\n";
+        let segments = [Segment::new(0, None)];
+        let error = process(program, &segments, &[(0x000, 0x1000)]).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::NoRoomForSegment));
+    }
+
+    #[test]
+    fn given_base_in_reserved_range_should_fail() {
+        let program = "4000 0000\n4002 0001\n";
+        let segments = [Segment::new(0, Some(0x0FE))];
+        let error = process(program, &segments, &[(0x000, 0x100)]).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::SegmentInReservedRange));
+        assert_eq!(
+            error.message.as_deref().unwrap(),
+            "segment relocated to base /0FE overlaps reserved range /000-/0FF"
+        );
+        let (_, bases) = process(program, &segments, &[(0x000, 0x0FE)]).unwrap();
+        assert_eq!(bases, [0x0FE]);
+    }
+
+    #[test]
+    fn imports_should_fail() {
+        let program = indoc! {"
+            5000 A000
+            1000 0000 ; < FOO
+            1000 0001 ; < BAR
+        "};
+        let error = process(program, &[Segment::new(0, Some(0))], &[]).unwrap_err();
+        assert_eq!(error.code, Some(ErrorCode::ImportsOnRelocation));
+        assert_eq!(
+            error.message.as_deref().unwrap(),
+            "program still imports unresolved symbols: FOO, BAR"
+        );
+        assert_eq!(error.labels.len(), 1);
     }
-    Ok(instructions)
 }
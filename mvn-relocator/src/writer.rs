@@ -1,20 +1,107 @@
+use std::io::{self, Write};
+
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use machine_code::image;
 use utils::error::MvnReportError;
+use utils::i18n::Message;
+use utils::io::Source;
+use utils::report::{exit_with_errors, MessageFormat};
+
+use crate::types::{AddressedProgram, Operand};
 
-use crate::types::AddressedProgram;
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImageFormat {
+    #[default]
+    Mvn,
+    Hex,
+    Bin,
+    Verilog,
+    Vhdl,
+}
 
-pub fn print(processor_output: Result<AddressedProgram, MvnReportError>) {
+// Implemented by hand so that the help of each value is translated
+impl ValueEnum for ImageFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Mvn, Self::Hex, Self::Bin, Self::Verilog, Self::Vhdl]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Mvn => PossibleValue::new("mvn").help(Message::HelpFormatMvn.to_string()),
+            Self::Hex => PossibleValue::new("hex").help(Message::HelpFormatHex.to_string()),
+            Self::Bin => PossibleValue::new("bin").help(Message::HelpFormatBin.to_string()),
+            Self::Verilog => {
+                PossibleValue::new("verilog").help(Message::HelpFormatVerilog.to_string())
+            }
+            Self::Vhdl => PossibleValue::new("vhdl").help(Message::HelpFormatVhdl.to_string()),
+        })
+    }
+}
+
+pub fn print(
+    source: Source,
+    processor_output: Result<AddressedProgram, MvnReportError>,
+    keep_exports: bool,
+    format: ImageFormat,
+    fill: u8,
+    message_format: MessageFormat,
+) {
     match processor_output {
-        Ok(program) => print_program(program),
-        Err(error) => print_error(error),
+        Ok(program) if format == ImageFormat::Mvn => print_program(program, keep_exports),
+        Ok(program) => print_image(&program, format, fill),
+        Err(error) => exit_with_errors(&[error], &[source], message_format),
     }
 }
 
-fn print_error(error: MvnReportError) {
-    eprintln!("{error:#?}");
+fn print_program(program: AddressedProgram, keep_exports: bool) {
+    print!("{}", render(&program, keep_exports));
+}
+
+/// Relocated program, one word per line, followed by its exports if kept
+/// and the labels placing no word
+pub fn render(program: &AddressedProgram, keep_exports: bool) -> String {
+    program
+        .lines
+        .iter()
+        .filter(|line| keep_exports || line.relational_annotation.is_none())
+        .map(|line| format!("{line}\n"))
+        .chain(program.labels.iter().map(|label| format!("{label}\n")))
+        .collect()
+}
+
+/// Writes an absolute program to stdout as a memory image, leaving out its symbol table
+pub fn print_image(program: &AddressedProgram, format: ImageFormat, fill: u8) {
+    match format {
+        ImageFormat::Mvn => {
+            for line in program.lines.iter() {
+                if line.relational_annotation.is_none() {
+                    println!("{line}");
+                }
+            }
+        }
+        ImageFormat::Hex => print!("{}", image::to_intel_hex(program)),
+        ImageFormat::Bin => io::stdout()
+            .write_all(&image::to_binary(program, fill))
+            .expect("failed to write to stdout"),
+        ImageFormat::Verilog => print!("{}", image::to_verilog(program)),
+        ImageFormat::Vhdl => print!("{}", image::to_vhdl(program, fill)),
+    }
 }
 
-fn print_program(program: AddressedProgram) {
-    for line in program.into_iter() {
-        println!("{line}");
+/// One exported symbol per line along with its relocated address
+pub fn render_symbols(program: &AddressedProgram) -> String {
+    let mut symbols = String::new();
+    for line in program.lines.iter() {
+        let Some(annotation) = &line.relational_annotation else {
+            continue;
+        };
+        if let (Operand::Symbolic(label), Operand::Numeric(position)) = (
+            &annotation.operation.operand.value,
+            &line.operation.operand.value,
+        ) {
+            symbols.push_str(&format!("{label} /{position:03X}\n"));
+        }
     }
+    symbols
 }
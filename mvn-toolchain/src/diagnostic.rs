@@ -0,0 +1,143 @@
+use std::error::Error;
+use std::fmt;
+
+use serde::Serialize;
+use utils::code::ErrorCode;
+use utils::error::{self, MvnReportError};
+
+/*
+ * Owned copies of the errors reported by the tools, detached from the
+ * sources they point to. The fields match the diagnostics written by
+ * `--message-format json`, so both may be handled the same way
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Identifier such as `E0101`, explained by `mvn-cli explain`
+    pub code: Option<String>,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: u32,
+    pub column: usize,
+    pub span_length: usize,
+    pub labels: Vec<DiagnosticLabel>,
+    pub help: Vec<String>,
+}
+
+/// Secondary place related to a diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct DiagnosticLabel {
+    pub message: String,
+    pub file: Option<String>,
+    pub line: u32,
+    pub column: usize,
+    pub span_length: usize,
+}
+
+impl Diagnostic {
+    // Errors that don't name their file come from the only input available
+    pub(crate) fn new(error: MvnReportError, file: Option<&str>) -> Self {
+        let error = error.into_report();
+        let file = error.file.or_else(|| file.map(str::to_owned));
+        Self {
+            severity: match error.severity {
+                error::Severity::Error => Severity::Error,
+                error::Severity::Warning => Severity::Warning,
+            },
+            code: error.code.as_ref().map(ErrorCode::code).map(str::to_owned),
+            message: error.message.unwrap_or_default(),
+            line: error.position.line,
+            column: error.position.column,
+            span_length: error.span.len(),
+            labels: error
+                .labels
+                .into_iter()
+                .map(|label| DiagnosticLabel {
+                    message: label.message,
+                    file: label.file.or_else(|| file.clone()),
+                    line: label.position.line,
+                    column: label.position.column,
+                    span_length: label.span.len(),
+                })
+                .collect(),
+            help: error.help,
+            file,
+        }
+    }
+}
+
+/// Written as `FILE:LINE:COLUMN: error[CODE]: MESSAGE`
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}:")?;
+        }
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}:{}: {severity}", self.line, self.column)?;
+        if let Some(code) = &self.code {
+            write!(f, "[{code}]")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Every diagnostic reported by a failed step, never empty
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    pub(crate) fn new(errors: Vec<MvnReportError>, file: Option<&str>) -> Self {
+        Self(
+            errors
+                .into_iter()
+                .map(|error| Diagnostic::new(error, file))
+                .collect(),
+        )
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// One diagnostic per line
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.iter().map(Diagnostic::to_string).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Error for Diagnostics {}
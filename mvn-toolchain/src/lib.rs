@@ -0,0 +1,253 @@
+pub mod diagnostic;
+
+pub use diagnostic::{Diagnostic, DiagnosticLabel, Diagnostics, Severity};
+
+use std::fmt;
+use std::path::Path;
+
+use linker::object::ObjectProgram;
+use linker::types::uppercase_program;
+use linker::writer::Dialect;
+use relocator::processor::Segment;
+use utils::io::Source;
+
+/*
+ * Entry point for programs embedding the toolchain, such as graders and
+ * editor plugins. Each step takes and returns programs in memory, in the
+ * same text format written by the command line tools, and fails with
+ * every diagnostic it reported. Only the types in this crate are part of
+ * its interface, so the crates behind it may change freely
+ */
+
+/// Program in machine code, either intermediate (`.int`), linked (`.lig`)
+/// or absolute (`.mvn`), along with the name used in its diagnostics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    name: String,
+    code: String,
+}
+
+impl Object {
+    /// Code is read in uppercase, as by the command line tools
+    pub fn new(name: impl Into<String>, code: impl AsRef<str>) -> Self {
+        Self {
+            name: name.into(),
+            code: uppercase_program(code.as_ref()),
+        }
+    }
+
+    /// Object written by `mvn-cli link --format json`
+    pub fn from_json(name: impl Into<String>, json: &str) -> Result<Self, Diagnostics> {
+        let name = name.into();
+        match ObjectProgram::from_json(json) {
+            Ok(object) => Ok(Self::new(name, object.to_string())),
+            Err(error) => Err(Diagnostics::new(vec![error], Some(&name))),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, Diagnostics> {
+        let program = linker::processor::parse(Source::new(&self.name, &self.code))
+            .map_err(|error| Diagnostics::new(vec![error], Some(&self.name)))?;
+        Ok(ObjectProgram::new(&program.lines).to_json())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AssembleOptions {
+    /// Name of the source in diagnostics, the object taking it with `.int`
+    pub name: String,
+    /// Follow each word with the place in the source it was assembled from
+    pub debug: bool,
+}
+
+impl Default for AssembleOptions {
+    fn default() -> Self {
+        Self {
+            name: "program.asm".to_owned(),
+            debug: false,
+        }
+    }
+}
+
+impl AssembleOptions {
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..self
+        }
+    }
+
+    pub fn with_debug(self, debug: bool) -> Self {
+        Self { debug, ..self }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LinkOptions {
+    /// Name of the linked object
+    pub name: String,
+    /// Fail on unresolved imports and leave out the symbol table
+    pub complete: bool,
+    /// Leave out programs no other program refers to
+    pub gc: bool,
+}
+
+impl Default for LinkOptions {
+    fn default() -> Self {
+        Self {
+            name: "program.lig".to_owned(),
+            complete: false,
+            gc: false,
+        }
+    }
+}
+
+impl LinkOptions {
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..self
+        }
+    }
+
+    pub fn with_complete(self, complete: bool) -> Self {
+        Self { complete, ..self }
+    }
+
+    pub fn with_gc(self, gc: bool) -> Self {
+        Self { gc, ..self }
+    }
+}
+
+/// Intermediate code of an assembly source
+pub fn assemble(source: &str, options: &AssembleOptions) -> Result<Object, Diagnostics> {
+    let source = source.to_uppercase();
+    let name = options.name.as_str();
+    let (program, label_map) = assembler::processor::process(&source)
+        .map_err(|errors| Diagnostics::new(errors, Some(name)))?;
+    let code = assembler::writer::render(program, label_map, options.debug.then_some(name));
+    let object_name = Path::new(name).with_extension("int");
+    Ok(Object::new(object_name.display().to_string(), code))
+}
+
+pub fn link(objects: &[Object], options: &LinkOptions) -> Result<Object, Diagnostics> {
+    let sources: Vec<Source> = objects
+        .iter()
+        .map(|object| Source::new(&object.name, &object.code))
+        .collect();
+    let processor =
+        linker::processor::process(&sources, &[], None, None, options.gc, options.complete)
+            .map_err(|error| {
+                let file = match objects {
+                    [object] => Some(object.name.as_str()),
+                    _ => None,
+                };
+                Diagnostics::new(vec![error], file)
+            })?;
+    let code = linker::writer::render(processor, options.complete, Dialect::Mvn);
+    Ok(Object::new(&options.name, code))
+}
+
+/// Absolute program with the relocatable code of `object` starting at `base`,
+/// its symbol table left out
+pub fn relocate(object: &Object, base: u16) -> Result<Object, Diagnostics> {
+    let segments = [Segment::new(0, Some(base.into()))];
+    let (program, _) = relocator::processor::process(&object.code, &segments, &[])
+        .map_err(|error| Diagnostics::new(vec![error], Some(&object.name)))?;
+    let code = relocator::writer::render(&program, false);
+    let name = Path::new(&object.name).with_extension("mvn");
+    Ok(Object::new(name.display().to_string(), code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const MAIN: &str = indoc! {"
+                < TWICE
+                &   /0
+        MAIN    LV  /2
+                SC  TWICE
+                HM  MAIN
+                # MAIN
+    "};
+
+    const TWICE: &str = indoc! {"
+                > TWICE
+                &   /0
+        TWICE   $   /1
+                MM  VALUE
+                AD  VALUE
+                RS  TWICE
+        VALUE   K   /0
+                # TWICE
+    "};
+
+    #[test]
+    fn should_assemble_link_and_relocate() {
+        let main = assemble(MAIN, &AssembleOptions::default().with_name("main.asm")).unwrap();
+        let twice = assemble(TWICE, &AssembleOptions::default().with_name("twice.asm")).unwrap();
+        assert_eq!(main.name(), "main.int");
+
+        let options = LinkOptions::default().with_complete(true);
+        let linked = link(&[main, twice], &options).unwrap();
+        let program = relocate(&linked, 0x100).unwrap();
+        assert_eq!(program.name(), "program.mvn");
+        assert_eq!(
+            program.code(),
+            indoc! {"
+                0100 3002
+                0102 A106
+                0104 C100
+                0108 910E
+                010A 410E
+                010C B106
+                010E 0000
+            "}
+        );
+    }
+
+    #[test]
+    fn assembly_errors_should_be_diagnostics() {
+        let options = AssembleOptions::default().with_name("main.asm");
+        let diagnostics = assemble("        FOO /0\n", &options).unwrap_err();
+        let diagnostic = diagnostics.iter().next().unwrap();
+        assert_eq!(diagnostic.code.as_deref(), Some("E0102"));
+        assert_eq!(diagnostic.file.as_deref(), Some("main.asm"));
+        assert_eq!(diagnostic.line, 1);
+    }
+
+    #[test]
+    fn unresolved_imports_should_fail_on_complete_linkage() {
+        let main = assemble(MAIN, &AssembleOptions::default().with_name("main.asm")).unwrap();
+        let options = LinkOptions::default().with_complete(true);
+        let diagnostics = link(&[main], &options).unwrap_err();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics.to_string().starts_with("main.int:"));
+    }
+
+    #[test]
+    fn objects_should_round_trip_through_json() {
+        let main = assemble(MAIN, &AssembleOptions::default()).unwrap();
+        let json = main.to_json().unwrap();
+        assert_eq!(Object::from_json("program.int", &json).unwrap(), main);
+    }
+}
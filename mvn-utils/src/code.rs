@@ -0,0 +1,254 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::i18n::{lang, Lang, Message};
+
+/*
+ * Stable identifiers for every diagnostic, so that they can be
+ * looked up with `mvn-cli explain` regardless of how the message
+ * itself is worded. Codes are grouped by the stage reporting them:
+ * E01xx for parsing, E02xx for assembling, E03xx for linking, E04xx
+ * for relocating and E05xx for converting between formats. Codes must
+ * never be reused nor renumbered
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    InvalidLabel,
+    UnknownMneumonic,
+    InvalidImmediate,
+    MalformedLine,
+    InvalidAddressProperties,
+    NumericOperandOnImportExport,
+    SymbolicOperandOnPositional,
+    UndefinedLabel,
+    AddressOutsideMemory,
+    ImmediateTooWide,
+    ConstantTooWide,
+    InvalidImportedOperand,
+    OperandNotImported,
+    DuplicateExport,
+    ImportsOnRelocation,
+    MalformedArchive,
+    MalformedLinkerScript,
+    RegionOverflow,
+    OverlappingRegions,
+    OverlappingPrograms,
+    ProgramOutsideMemory,
+    RelocatedOutsideMemory,
+    OverlappingRelocatedWords,
+    UnresolvedSymbols,
+    SymbolNameConflict,
+    NoRoomForProgram,
+    NoRoomForSegment,
+    InvalidHexRecord,
+    NotAbsoluteOnConversion,
+    MalformedObject,
+    ArchiveMemberNotFound,
+    SegmentInReservedRange,
+}
+
+impl ErrorCode {
+    pub const ALL: [Self; 32] = [
+        Self::InvalidLabel,
+        Self::UnknownMneumonic,
+        Self::InvalidImmediate,
+        Self::MalformedLine,
+        Self::InvalidAddressProperties,
+        Self::NumericOperandOnImportExport,
+        Self::SymbolicOperandOnPositional,
+        Self::UndefinedLabel,
+        Self::AddressOutsideMemory,
+        Self::ImmediateTooWide,
+        Self::ConstantTooWide,
+        Self::InvalidImportedOperand,
+        Self::OperandNotImported,
+        Self::DuplicateExport,
+        Self::ImportsOnRelocation,
+        Self::MalformedArchive,
+        Self::MalformedLinkerScript,
+        Self::RegionOverflow,
+        Self::OverlappingRegions,
+        Self::OverlappingPrograms,
+        Self::ProgramOutsideMemory,
+        Self::RelocatedOutsideMemory,
+        Self::OverlappingRelocatedWords,
+        Self::UnresolvedSymbols,
+        Self::SymbolNameConflict,
+        Self::NoRoomForProgram,
+        Self::NoRoomForSegment,
+        Self::InvalidHexRecord,
+        Self::NotAbsoluteOnConversion,
+        Self::MalformedObject,
+        Self::ArchiveMemberNotFound,
+        Self::SegmentInReservedRange,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidLabel => "E0101",
+            Self::UnknownMneumonic => "E0102",
+            Self::InvalidImmediate => "E0103",
+            Self::MalformedLine => "E0104",
+            Self::InvalidAddressProperties => "E0105",
+            Self::NumericOperandOnImportExport => "E0201",
+            Self::SymbolicOperandOnPositional => "E0202",
+            Self::UndefinedLabel => "E0203",
+            Self::AddressOutsideMemory => "E0204",
+            Self::ImmediateTooWide => "E0205",
+            Self::ConstantTooWide => "E0206",
+            Self::InvalidImportedOperand => "E0301",
+            Self::OperandNotImported => "E0302",
+            Self::DuplicateExport => "E0303",
+            Self::ImportsOnRelocation => "E0401",
+            Self::MalformedArchive => "E0304",
+            Self::MalformedLinkerScript => "E0305",
+            Self::RegionOverflow => "E0306",
+            Self::OverlappingRegions => "E0307",
+            Self::OverlappingPrograms => "E0308",
+            Self::ProgramOutsideMemory => "E0309",
+            Self::RelocatedOutsideMemory => "E0402",
+            Self::OverlappingRelocatedWords => "E0403",
+            Self::UnresolvedSymbols => "E0310",
+            Self::SymbolNameConflict => "E0311",
+            Self::NoRoomForProgram => "E0312",
+            Self::NoRoomForSegment => "E0404",
+            Self::InvalidHexRecord => "E0501",
+            Self::NotAbsoluteOnConversion => "E0502",
+            Self::MalformedObject => "E0106",
+            Self::ArchiveMemberNotFound => "E0313",
+            Self::SegmentInReservedRange => "E0405",
+        }
+    }
+
+    /// Explanation in the language set with `set_lang`
+    pub fn explanation(&self) -> &'static str {
+        match lang() {
+            Lang::En => self.explanation_en(),
+            Lang::PtBr => self.explanation_pt_br(),
+        }
+    }
+
+    fn explanation_en(&self) -> &'static str {
+        match self {
+            Self::InvalidLabel => include_str!("../explanations/E0101.md"),
+            Self::UnknownMneumonic => include_str!("../explanations/E0102.md"),
+            Self::InvalidImmediate => include_str!("../explanations/E0103.md"),
+            Self::MalformedLine => include_str!("../explanations/E0104.md"),
+            Self::InvalidAddressProperties => include_str!("../explanations/E0105.md"),
+            Self::NumericOperandOnImportExport => include_str!("../explanations/E0201.md"),
+            Self::SymbolicOperandOnPositional => include_str!("../explanations/E0202.md"),
+            Self::UndefinedLabel => include_str!("../explanations/E0203.md"),
+            Self::AddressOutsideMemory => include_str!("../explanations/E0204.md"),
+            Self::ImmediateTooWide => include_str!("../explanations/E0205.md"),
+            Self::ConstantTooWide => include_str!("../explanations/E0206.md"),
+            Self::InvalidImportedOperand => include_str!("../explanations/E0301.md"),
+            Self::OperandNotImported => include_str!("../explanations/E0302.md"),
+            Self::DuplicateExport => include_str!("../explanations/E0303.md"),
+            Self::ImportsOnRelocation => include_str!("../explanations/E0401.md"),
+            Self::MalformedArchive => include_str!("../explanations/E0304.md"),
+            Self::MalformedLinkerScript => include_str!("../explanations/E0305.md"),
+            Self::RegionOverflow => include_str!("../explanations/E0306.md"),
+            Self::OverlappingRegions => include_str!("../explanations/E0307.md"),
+            Self::OverlappingPrograms => include_str!("../explanations/E0308.md"),
+            Self::ProgramOutsideMemory => include_str!("../explanations/E0309.md"),
+            Self::RelocatedOutsideMemory => include_str!("../explanations/E0402.md"),
+            Self::OverlappingRelocatedWords => include_str!("../explanations/E0403.md"),
+            Self::UnresolvedSymbols => include_str!("../explanations/E0310.md"),
+            Self::SymbolNameConflict => include_str!("../explanations/E0311.md"),
+            Self::NoRoomForProgram => include_str!("../explanations/E0312.md"),
+            Self::NoRoomForSegment => include_str!("../explanations/E0404.md"),
+            Self::InvalidHexRecord => include_str!("../explanations/E0501.md"),
+            Self::NotAbsoluteOnConversion => include_str!("../explanations/E0502.md"),
+            Self::MalformedObject => include_str!("../explanations/E0106.md"),
+            Self::ArchiveMemberNotFound => include_str!("../explanations/E0313.md"),
+            Self::SegmentInReservedRange => include_str!("../explanations/E0405.md"),
+        }
+    }
+
+    fn explanation_pt_br(&self) -> &'static str {
+        match self {
+            Self::InvalidLabel => include_str!("../explanations/pt-br/E0101.md"),
+            Self::UnknownMneumonic => include_str!("../explanations/pt-br/E0102.md"),
+            Self::InvalidImmediate => include_str!("../explanations/pt-br/E0103.md"),
+            Self::MalformedLine => include_str!("../explanations/pt-br/E0104.md"),
+            Self::InvalidAddressProperties => include_str!("../explanations/pt-br/E0105.md"),
+            Self::NumericOperandOnImportExport => include_str!("../explanations/pt-br/E0201.md"),
+            Self::SymbolicOperandOnPositional => include_str!("../explanations/pt-br/E0202.md"),
+            Self::UndefinedLabel => include_str!("../explanations/pt-br/E0203.md"),
+            Self::AddressOutsideMemory => include_str!("../explanations/pt-br/E0204.md"),
+            Self::ImmediateTooWide => include_str!("../explanations/pt-br/E0205.md"),
+            Self::ConstantTooWide => include_str!("../explanations/pt-br/E0206.md"),
+            Self::InvalidImportedOperand => include_str!("../explanations/pt-br/E0301.md"),
+            Self::OperandNotImported => include_str!("../explanations/pt-br/E0302.md"),
+            Self::DuplicateExport => include_str!("../explanations/pt-br/E0303.md"),
+            Self::ImportsOnRelocation => include_str!("../explanations/pt-br/E0401.md"),
+            Self::MalformedArchive => include_str!("../explanations/pt-br/E0304.md"),
+            Self::MalformedLinkerScript => include_str!("../explanations/pt-br/E0305.md"),
+            Self::RegionOverflow => include_str!("../explanations/pt-br/E0306.md"),
+            Self::OverlappingRegions => include_str!("../explanations/pt-br/E0307.md"),
+            Self::OverlappingPrograms => include_str!("../explanations/pt-br/E0308.md"),
+            Self::ProgramOutsideMemory => include_str!("../explanations/pt-br/E0309.md"),
+            Self::RelocatedOutsideMemory => include_str!("../explanations/pt-br/E0402.md"),
+            Self::OverlappingRelocatedWords => include_str!("../explanations/pt-br/E0403.md"),
+            Self::UnresolvedSymbols => include_str!("../explanations/pt-br/E0310.md"),
+            Self::SymbolNameConflict => include_str!("../explanations/pt-br/E0311.md"),
+            Self::NoRoomForProgram => include_str!("../explanations/pt-br/E0312.md"),
+            Self::NoRoomForSegment => include_str!("../explanations/pt-br/E0404.md"),
+            Self::InvalidHexRecord => include_str!("../explanations/pt-br/E0501.md"),
+            Self::NotAbsoluteOnConversion => include_str!("../explanations/pt-br/E0502.md"),
+            Self::MalformedObject => include_str!("../explanations/pt-br/E0106.md"),
+            Self::ArchiveMemberNotFound => include_str!("../explanations/pt-br/E0313.md"),
+            Self::SegmentInReservedRange => include_str!("../explanations/pt-br/E0405.md"),
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|code| code.code().eq_ignore_ascii_case(s))
+            .ok_or_else(|| Message::UnknownErrorCode(s).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn codes_should_be_unique() {
+        let mut codes: Vec<&str> = ErrorCode::ALL.iter().map(ErrorCode::code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), ErrorCode::ALL.len());
+    }
+
+    #[test]
+    fn should_parse_code() {
+        assert_eq!("E0203".parse(), Ok(ErrorCode::UndefinedLabel));
+        assert_eq!("e0203".parse(), Ok(ErrorCode::UndefinedLabel));
+        assert!("E9999".parse::<ErrorCode>().is_err());
+    }
+
+    #[test]
+    fn every_code_should_be_explained() {
+        for code in ErrorCode::ALL {
+            let explanation = code.explanation_en();
+            assert!(explanation.contains("Erroneous example"), "{code}");
+            assert!(explanation.contains("Corrected example"), "{code}");
+            let explanation = code.explanation_pt_br();
+            assert!(explanation.contains("Exemplo errado"), "{code}");
+            assert!(explanation.contains("Exemplo corrigido"), "{code}");
+        }
+    }
+}
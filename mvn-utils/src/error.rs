@@ -1,32 +1,177 @@
 use std::convert::From;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
 
 use nom;
 use nom_locate::LocatedSpan;
+use serde::Serialize;
 
-use super::types::Position;
+use super::code::ErrorCode;
+use super::i18n::Message;
+use super::types::{Position, SourceMap, SourceSpan};
 
 pub type Span<'a> = LocatedSpan<&'a str>;
 pub type LocatedError<'a> = nom::error::Error<Span<'a>>;
 pub type LocatedIResult<'a, O> = nom::IResult<Span<'a>, O, MvnParseError<'a>>;
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/*
+ * Secondary locations related to an error, such as the place
+ * a conflicting symbol was first defined. A label without a
+ * file refers to the same file as the error it is attached to
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct MvnReportLabel {
+    pub file: Option<String>,
+    pub position: Position,
+    pub span: SourceSpan,
+    pub message: String,
+}
+
+impl MvnReportLabel {
+    pub fn new(file: Option<String>, position: Position, message: String) -> Self {
+        Self {
+            file,
+            position,
+            span: SourceSpan::default(),
+            message,
+        }
+    }
+
+    pub fn with_span(self, span: SourceSpan) -> Self {
+        Self { span, ..self }
+    }
+}
+
+/*
+ * Diagnostics carry spans, secondary labels and help notes, so their
+ * contents are boxed to keep every `Result` returning them small. The
+ * fields are still reached directly on the error, through `Deref`
+ */
 #[derive(Debug)]
-pub struct MvnReportError {
+pub struct MvnReportError(Box<MvnReport>);
+
+#[derive(Debug)]
+pub struct MvnReport {
+    pub code: Option<ErrorCode>,
     pub position: Position,
+    pub span: SourceSpan,
     pub message: Option<String>,
+    pub severity: Severity,
+    pub file: Option<String>,
+    pub labels: Vec<MvnReportLabel>,
+    pub help: Vec<String>,
+}
+
+impl Deref for MvnReportError {
+    type Target = MvnReport;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for MvnReportError {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
 }
 
 impl From<MvnParseError<'_>> for MvnReportError {
     fn from(value: MvnParseError) -> Self {
-        Self {
-            position: value.span.into(),
-            message: value.message,
+        // Parse errors point to the remaining input, so only the
+        // word the parser stopped at gets underlined
+        let word = value
+            .span
+            .fragment()
+            .split(char::is_whitespace)
+            .next()
+            .unwrap_or_default();
+        let start = value.span.location_offset();
+        let error = Self::new(value.span.into(), value.message)
+            .with_code(value.code)
+            .with_span(SourceSpan::new(start, start + word.len()));
+        match value.help {
+            Some(help) => error.with_help(help),
+            None => error,
         }
     }
 }
 
 impl MvnReportError {
     pub fn new(position: Position, message: Option<String>) -> Self {
-        Self { position, message }
+        Self(Box::new(MvnReport {
+            code: None,
+            position,
+            span: SourceSpan::default(),
+            message,
+            severity: Severity::default(),
+            file: None,
+            labels: Vec::new(),
+            help: Vec::new(),
+        }))
+    }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_span(mut self, span: SourceSpan) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn with_file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_owned());
+        self
+    }
+
+    pub fn with_label(mut self, label: MvnReportLabel) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Self {
+        self.help.push(help);
+        self
+    }
+
+    /// Error found in a rewritten input pointed back to its original. Only the
+    /// error and labels reported in `file` are moved, labels without a file
+    /// being reported in the same one as their error
+    pub fn locate(mut self, map: &SourceMap, file: Option<&str>) -> Self {
+        if self.file.as_deref() != file {
+            return self;
+        }
+        (self.position, self.span) = map.locate(self.position, self.span);
+        for label in self.0.labels.iter_mut() {
+            if label.file.is_none() || label.file.as_deref() == file {
+                (label.position, label.span) = map.locate(label.position, label.span);
+            }
+        }
+        self
+    }
+
+    /// Contents of the error, so that its fields may be moved out of it
+    pub fn into_report(self) -> MvnReport {
+        *self.0
     }
 }
 
@@ -40,19 +185,35 @@ impl MvnReportError {
 
 #[derive(Debug, PartialEq)]
 pub struct MvnParseError<'a> {
+    pub code: ErrorCode,
     pub span: Span<'a>,
     pub message: Option<String>,
+    pub help: Option<String>,
 }
 
 impl<'a> MvnParseError<'a> {
+    // Errors raised by nom itself only say the line is malformed
     pub fn new(message: String, span: Span<'a>) -> Self {
         Self {
+            code: ErrorCode::MalformedLine,
             span,
             message: Some(message),
+            help: None,
+        }
+    }
+
+    pub fn with_code(self, code: ErrorCode) -> Self {
+        Self { code, ..self }
+    }
+
+    pub fn with_help(self, help: String) -> Self {
+        Self {
+            help: Some(help),
+            ..self
         }
     }
 
-    pub fn span(&self) -> &Span {
+    pub fn span(&self) -> &Span<'a> {
         &self.span
     }
 
@@ -68,7 +229,7 @@ impl<'a> MvnParseError<'a> {
 // That's what makes it nom-compatible.
 impl<'a> nom::error::ParseError<Span<'a>> for MvnParseError<'a> {
     fn from_error_kind(input: Span<'a>, kind: nom::error::ErrorKind) -> Self {
-        Self::new(format!("parse error {:?}", kind), input)
+        Self::new(Message::ParseError(&format!("{kind:?}")).to_string(), input)
     }
 
     fn append(_input: Span<'a>, _kind: nom::error::ErrorKind, other: Self) -> Self {
@@ -76,19 +237,18 @@ impl<'a> nom::error::ParseError<Span<'a>> for MvnParseError<'a> {
     }
 
     fn from_char(input: Span<'a>, c: char) -> Self {
-        Self::new(format!("unexpected character '{}'", c), input)
+        Self::new(Message::UnexpectedCharacter(c).to_string(), input)
     }
 }
 
 #[macro_export]
 macro_rules! error_or {
-    ($result:expr, $input:expr, $message:literal) => {
+    ($result:expr, $input:expr, $code:expr, $message:expr) => {
         match $result {
             Ok((rest, content)) => Ok((rest, content)),
-            Err(nom::Err::Error(_)) => Err(nom::Err::Error(utils::error::MvnParseError::new(
-                $message.to_owned(),
-                $input,
-            ))),
+            Err(nom::Err::Error(_)) => Err(nom::Err::Error(
+                utils::error::MvnParseError::new($message.to_string(), $input).with_code($code),
+            )),
             Err(e) => Err(e),
         }
     };
@@ -96,13 +256,12 @@ macro_rules! error_or {
 
 #[macro_export]
 macro_rules! failure_or {
-    ($result:expr, $input:expr, $message:literal) => {
+    ($result:expr, $input:expr, $code:expr, $message:expr) => {
         match $result {
             Ok((rest, content)) => Ok((rest, content)),
-            Err(nom::Err::Error(_)) => Err(nom::Err::Failure(utils::error::MvnParseError::new(
-                $message.to_owned(),
-                $input,
-            ))),
+            Err(nom::Err::Error(_)) => Err(nom::Err::Failure(
+                utils::error::MvnParseError::new($message.to_string(), $input).with_code($code),
+            )),
             Err(e) => Err(e),
         }
     };
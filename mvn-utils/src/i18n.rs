@@ -0,0 +1,751 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    PtBr,
+}
+
+impl Lang {
+    /*
+     * Locales follow the POSIX `language_TERRITORY.encoding` format,
+     * and any Portuguese variant is better served by pt-BR than en
+     */
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let language = locale.split(['_', '-', '.', '@']).next()?;
+        match language.to_lowercase().as_str() {
+            "pt" => Some(Self::PtBr),
+            "en" | "c" | "posix" => Some(Self::En),
+            _ => None,
+        }
+    }
+
+    /*
+     * Help texts are built while the command line is parsed, so
+     * `--lang` is looked up in the raw arguments beforehand, falling
+     * back to the locale environment variables
+     */
+    pub fn detect(args: impl IntoIterator<Item = String>) -> Self {
+        Self::detect_with(args, |variable| std::env::var(variable).ok())
+    }
+
+    // Environment variables are looked up through `var`, so that tests don't touch them
+    fn detect_with(
+        args: impl IntoIterator<Item = String>,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = match arg.strip_prefix("--lang") {
+                Some("") => args.next(),
+                Some(value) => value.strip_prefix('=').map(str::to_owned),
+                None => None,
+            };
+            if let Some(lang) = value.and_then(|value| Self::from_str(&value, true).ok()) {
+                return lang;
+            }
+        }
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(var)
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| Self::from_locale(&locale))
+            .unwrap_or_default()
+    }
+}
+
+impl ValueEnum for Lang {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::En, Self::PtBr]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::En => PossibleValue::new("en").help(Message::HelpLangEn.to_string()),
+            Self::PtBr => PossibleValue::new("pt-br")
+                .alias("pt")
+                .alias("pt_BR")
+                .help(Message::HelpLangPtBr.to_string()),
+        })
+    }
+}
+
+static LANG: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn lang() -> Lang {
+    match LANG.load(Ordering::Relaxed) {
+        1 => Lang::PtBr,
+        _ => Lang::En,
+    }
+}
+
+/*
+ * Every message shown to users, so that all of them can be
+ * translated. Messages are rendered in the language set with
+ * `set_lang` when formatted
+ */
+#[derive(Debug, Clone, Copy)]
+pub enum Message<'a> {
+    // Parsing
+    InvalidLabel,
+    UnknownInstruction,
+    UnknownPseudoInstruction,
+    UnknownMneumonic(&'a str),
+    ExpectedMneumonic,
+    InvalidImmediate,
+    InvalidAddressProperties,
+    ParseError(&'a str),
+    UnexpectedCharacter(char),
+    DidYouMean(&'a str),
+    // Assembling
+    NumericOperandOnImportExport,
+    SymbolicOperandOnPositional,
+    UndefinedLabel,
+    AddressOutsideMemory,
+    ConstantTooWide,
+    ImmediateTooWide,
+    // Linking
+    InvalidImportedOperand,
+    OperandNotImported,
+    DuplicateExport(&'a str),
+    MalformedArchive,
+    ArchiveMemberNotFound(&'a str),
+    UnsafeMemberName(&'a str),
+    ExpectedRegion,
+    InvalidRegion,
+    RegionEndsBeforeStart,
+    UnknownInput(&'a str),
+    InputDoesNotFit(&'a str, &'a str),
+    RegionOverlaps(&'a str),
+    RegionDeclaredHere,
+    UnexpectedWord(&'a str),
+    WordOutsideMemory(u32),
+    MalformedHexRecord,
+    HexChecksumMismatch,
+    UnsupportedHexRecord(u8),
+    MalformedObject(&'a str),
+    ObjectValueTooLarge(&'a str, u32, u32),
+    NotAbsoluteOnConversion(u32),
+    RelocateBeforeConverting,
+    OperandOutsideMemory(u32),
+    OverlappingWords(u32),
+    OtherWordPlacedHere,
+    UnresolvedSymbols(&'a str),
+    SymbolImportedHere(&'a str),
+    UndefinedReference(&'a str),
+    UndefinedReferenceAt(&'a str, &'a str),
+    InvalidRename(&'a str),
+    NoRoomForProgram(u32),
+    SegmentInReservedRange(u32, u32, u32),
+    InvalidReservedRange(&'a str),
+    CodeRelocatedTo(u32),
+    DataRelocatedTo(u32),
+    SymbolNameConflict(&'a str),
+    // Relocating
+    ImportsOnRelocation(&'a str),
+    LinkBeforeRelocating,
+    // Language server
+    HoverLabel(&'a str, u32),
+    HoverImport(&'a str, u32),
+    HoverRelocatable,
+    HoverExported,
+    HoverWord(&'a str),
+    // Formatter
+    NotFormatted(&'a str),
+    // Reports
+    ErrorWhileHandlingInput,
+    AbortingDueToErrors(usize),
+    ExplainThisError(&'a str),
+    SomeErrorsExplained(&'a str),
+    ExplainAnError(&'a str),
+    UnknownErrorCode(&'a str),
+    InputFileNotFound,
+    // Command line help
+    HelpAssemble,
+    HelpLink,
+    HelpRelocate,
+    HelpExplain,
+    HelpFmt,
+    HelpCheck,
+    HelpSources,
+    HelpLabelWidth,
+    HelpMneumonicWidth,
+    HelpOperandWidth,
+    HelpAr,
+    HelpArCreate,
+    HelpArList,
+    HelpArExtract,
+    HelpArchive,
+    HelpMembers,
+    HelpLibraries,
+    HelpMap,
+    HelpScript,
+    HelpGc,
+    HelpExport,
+    HelpObjdump,
+    HelpDebug,
+    HelpConvert,
+    HelpFormat,
+    HelpFormatMvn,
+    HelpDialect,
+    HelpFormatJson,
+    HelpFormatHex,
+    HelpFormatBin,
+    HelpFormatVerilog,
+    HelpFormatVhdl,
+    HelpFill,
+    HelpHide,
+    HelpRename,
+    HelpInput,
+    HelpInputOrStdin,
+    HelpInputs,
+    HelpPartial,
+    HelpComplete,
+    HelpBase,
+    HelpData,
+    HelpDataBase,
+    HelpReserve,
+    HelpKeepExports,
+    HelpSymbols,
+    HelpCode,
+    HelpMessageFormat,
+    HelpMessageFormatHuman,
+    HelpMessageFormatJson,
+    HelpLang,
+    HelpLangEn,
+    HelpLangPtBr,
+}
+
+impl Message<'_> {
+    pub fn translate(&self, lang: Lang) -> String {
+        match lang {
+            Lang::En => self.en(),
+            Lang::PtBr => self.pt_br(),
+        }
+    }
+
+    fn en(&self) -> String {
+        match self {
+            Self::InvalidLabel => {
+                "invalid label; perhaps you used non-ASCII characters or started with a number"
+                    .to_owned()
+            }
+            Self::UnknownInstruction => {
+                "mneumonic does not match that of any known instruction".to_owned()
+            }
+            Self::UnknownPseudoInstruction => {
+                "mneumonic does not match that of any known pseudo-instruction".to_owned()
+            }
+            Self::UnknownMneumonic(word) => format!("unknown mnemonic `{word}`"),
+            Self::ExpectedMneumonic => "expected a mnemonic".to_owned(),
+            Self::InvalidImmediate => "could not parse numeric immediate".to_owned(),
+            Self::InvalidAddressProperties => "invalid address properties".to_owned(),
+            Self::ParseError(kind) => format!("parse error {kind}"),
+            Self::UnexpectedCharacter(c) => format!("unexpected character '{c}'"),
+            Self::DidYouMean(suggestion) => format!("did you mean `{suggestion}`?"),
+            Self::NumericOperandOnImportExport => {
+                "numeric operand cannot be imported nor exported".to_owned()
+            }
+            Self::SymbolicOperandOnPositional => {
+                "symbolic operand cannot be used to reserve addresses or set positions".to_owned()
+            }
+            Self::UndefinedLabel => "undefined label used as operand".to_owned(),
+            Self::AddressOutsideMemory => "address outside memory".to_owned(),
+            Self::ConstantTooWide => {
+                "immediate over 16 bits for constant pseudoinstruction".to_owned()
+            }
+            Self::ImmediateTooWide => "immediate cannot be larger than 12 bits".to_owned(),
+            Self::InvalidImportedOperand => {
+                "can't replace numeric operand with position".to_owned()
+            }
+            Self::OperandNotImported => "operand marked as imported but not imported".to_owned(),
+            Self::DuplicateExport(symbol) => {
+                format!("symbol `{symbol}` is exported more than once")
+            }
+            Self::MalformedArchive => "malformed archive".to_owned(),
+            Self::ArchiveMemberNotFound(member) => format!("archive has no member `{member}`"),
+            Self::UnsafeMemberName(member) => {
+                format!("member name `{member}` must be a file name without directories")
+            }
+            Self::ExpectedRegion => "inputs must be listed under a region".to_owned(),
+            Self::InvalidRegion => {
+                "expected `REGION <name> <start> <end>`, optionally followed by `ALIGN <alignment>`"
+                    .to_owned()
+            }
+            Self::RegionEndsBeforeStart => "region ends before it starts".to_owned(),
+            Self::UnknownInput(input) => format!("no input named `{input}` was given"),
+            Self::InputDoesNotFit(input, region) => {
+                format!("`{input}` does not fit in region `{region}`")
+            }
+            Self::RegionOverlaps(region) => format!("region overlaps region `{region}`"),
+            Self::RegionDeclaredHere => "region declared here".to_owned(),
+            Self::UnexpectedWord(word) => format!("unexpected `{word}`"),
+            Self::MalformedHexRecord => "malformed Intel HEX record".to_owned(),
+            Self::HexChecksumMismatch => "Intel HEX record checksum doesn't match".to_owned(),
+            Self::UnsupportedHexRecord(kind) => {
+                format!("unsupported Intel HEX record type {kind:02X}")
+            }
+            Self::MalformedObject(error) => format!("malformed object file: {error}"),
+            Self::ObjectValueTooLarge(field, value, max) => {
+                format!("`{field}` is {value}, but can't be larger than /{max:X}")
+            }
+            Self::NotAbsoluteOnConversion(position) => {
+                format!("word at /{position:03X} isn't absolute")
+            }
+            Self::RelocateBeforeConverting => {
+                "relocate the program before converting it".to_owned()
+            }
+            Self::WordOutsideMemory(position) => {
+                format!("word placed at /{position:03X}, past the end of memory")
+            }
+            Self::OperandOutsideMemory(operand) => {
+                format!("operand relocated to /{operand:03X}, past the end of memory")
+            }
+            Self::OverlappingWords(position) => {
+                format!("word placed at /{position:03X} overlaps another word")
+            }
+            Self::OtherWordPlacedHere => "other word placed here".to_owned(),
+            Self::UnresolvedSymbols(symbols) => {
+                format!("complete linkage failed due to unresolved symbols: {symbols}")
+            }
+            Self::SymbolImportedHere(symbol) => format!("`{symbol}` imported here"),
+            Self::UndefinedReference(symbol) => format!("undefined reference to `{symbol}`"),
+            Self::UndefinedReferenceAt(symbol, location) => {
+                format!("undefined reference to {symbol} in {location}")
+            }
+            Self::InvalidRename(rename) => {
+                format!("expected `OLD=NEW` with valid symbol names, found `{rename}`")
+            }
+            Self::InvalidReservedRange(range) => {
+                format!("expected `START-END` with addresses up to /FFF, found `{range}`")
+            }
+            Self::CodeRelocatedTo(base) => format!("code relocated to base /{base:03X}"),
+            Self::DataRelocatedTo(base) => format!("data relocated to base /{base:03X}"),
+            Self::NoRoomForProgram(size) => {
+                format!("no room left in memory for /{size:03X} addresses of relocatable code")
+            }
+            Self::SegmentInReservedRange(base, start, end) => format!(
+                "segment relocated to base /{base:03X} overlaps reserved range /{start:03X}-/{end:03X}"
+            ),
+            Self::SymbolNameConflict(symbol) => {
+                format!("renaming makes more than one symbol be named `{symbol}`")
+            }
+            Self::ImportsOnRelocation(symbols) => {
+                format!("program still imports unresolved symbols: {symbols}")
+            }
+            Self::LinkBeforeRelocating => {
+                "link the program with `--complete` before relocating it".to_owned()
+            }
+            Self::HoverLabel(label, position) => format!("`{label}` at /{position:03X}"),
+            Self::HoverImport(label, index) => format!("`{label}` imported as symbol {index}"),
+            Self::HoverRelocatable => "relocatable".to_owned(),
+            Self::HoverExported => "exported".to_owned(),
+            Self::HoverWord(word) => format!("assembled into `{word}`"),
+            Self::NotFormatted(file) => format!("`{file}` is not formatted"),
+            Self::ErrorWhileHandlingInput => "error while handling input file".to_owned(),
+            Self::AbortingDueToErrors(1) => "aborting due to 1 previous error".to_owned(),
+            Self::AbortingDueToErrors(count) => format!("aborting due to {count} previous errors"),
+            Self::ExplainThisError(code) => {
+                format!("for more information about this error, try `mvn-cli explain {code}`")
+            }
+            Self::SomeErrorsExplained(codes) => {
+                format!("some errors have detailed explanations: {codes}")
+            }
+            Self::ExplainAnError(code) => {
+                format!("for more information about an error, try `mvn-cli explain {code}`")
+            }
+            Self::UnknownErrorCode(code) => format!("`{code}` is not a known error code"),
+            Self::InputFileNotFound => "input file does not exist".to_owned(),
+            Self::HelpAssemble => "Assemble a program into intermediate code".to_owned(),
+            Self::HelpLink => "Link programs in intermediate code".to_owned(),
+            Self::HelpRelocate => "Relocate a linked program to an absolute base".to_owned(),
+            Self::HelpExplain => "Explain an error code in detail".to_owned(),
+            Self::HelpFmt => "Align the columns of assembly sources".to_owned(),
+            Self::HelpCheck => {
+                "Only report the sources that are not formatted, failing if there are any"
+                    .to_owned()
+            }
+            Self::HelpSources => "Assembly sources, formatted in place".to_owned(),
+            Self::HelpLabelWidth => "Width of the label column".to_owned(),
+            Self::HelpMneumonicWidth => "Width of the mnemonic column".to_owned(),
+            Self::HelpOperandWidth => "Width of the operand column, before comments".to_owned(),
+            Self::HelpAr => "Create, list or extract archives of intermediate code".to_owned(),
+            Self::HelpArCreate => "Create an archive from programs in intermediate code".to_owned(),
+            Self::HelpArList => {
+                "List the members of an archive and the symbols they export".to_owned()
+            }
+            Self::HelpArExtract => {
+                "Extract members of an archive into the current directory".to_owned()
+            }
+            Self::HelpArchive => "Archive file".to_owned(),
+            Self::HelpMembers => "Archive members".to_owned(),
+            Self::HelpLibraries => "Archives from which only needed members are linked".to_owned(),
+            Self::HelpMap => "Write where each input and symbol ended up to a map file".to_owned(),
+            Self::HelpScript => "Linker script placing inputs at given addresses".to_owned(),
+            Self::HelpGc => {
+                "Drop code and data that can't be reached from the entry point or exports"
+                    .to_owned()
+            }
+            Self::HelpExport => "Keep only the given exports, hiding the others".to_owned(),
+            Self::HelpDebug => {
+                "Follow each word with the source line it was assembled from".to_owned()
+            }
+            Self::HelpConvert => {
+                "Convert an absolute program into a memory image, or Intel HEX back into a program"
+                    .to_owned()
+            }
+            Self::HelpFormat => "Format of the output".to_owned(),
+            Self::HelpFormatMvn => "MVN text, one address and word per line".to_owned(),
+            Self::HelpDialect => "Format of the linked program".to_owned(),
+            Self::HelpFormatJson => "JSON object with the symbol table and the words".to_owned(),
+            Self::HelpFormatHex => "Intel HEX records".to_owned(),
+            Self::HelpFormatBin => "Raw bytes from address 0, most significant first".to_owned(),
+            Self::HelpFormatVerilog => "Input to Verilog's $readmemh".to_owned(),
+            Self::HelpFormatVhdl => "VHDL constant initializing a memory".to_owned(),
+            Self::HelpFill => "Byte filling gaps in binary and VHDL images".to_owned(),
+            Self::HelpObjdump => {
+                "Describe each line of a program in intermediate, linked or machine code".to_owned()
+            }
+            Self::HelpHide => "Hide the given exports from later linking steps".to_owned(),
+            Self::HelpRename => "Rename an exported or imported symbol, as in OLD=NEW".to_owned(),
+            Self::HelpInput => "Input file".to_owned(),
+            Self::HelpInputOrStdin => "Input file, read from stdin if not given".to_owned(),
+            Self::HelpInputs => "Input files, in linking order".to_owned(),
+            Self::HelpPartial => "Keep unresolved imports for a later linking step".to_owned(),
+            Self::HelpComplete => "Require every import to be resolved".to_owned(),
+            Self::HelpKeepExports => {
+                "Keep exported symbols as annotations in the relocated program".to_owned()
+            }
+            Self::HelpSymbols => "Write the address of each exported symbol to a file".to_owned(),
+            Self::HelpBase => {
+                "Base address of the relocated program, the lowest free one if not given".to_owned()
+            }
+            Self::HelpData => "Address at which the data segment starts in the program".to_owned(),
+            Self::HelpDataBase => {
+                "Base address of the data segment, the lowest free one if not given".to_owned()
+            }
+            Self::HelpReserve => {
+                "Address range, such as 0x000-0x0FF, left out of automatic bases".to_owned()
+            }
+            Self::HelpCode => "Error code, such as E0203".to_owned(),
+            Self::HelpMessageFormat => "Format of the diagnostics".to_owned(),
+            Self::HelpMessageFormatHuman => {
+                "Annotated source snippets meant to be read by people".to_owned()
+            }
+            Self::HelpMessageFormatJson => {
+                "One JSON object per line for each diagnostic".to_owned()
+            }
+            Self::HelpLang => "Language of the messages".to_owned(),
+            Self::HelpLangEn => "English".to_owned(),
+            Self::HelpLangPtBr => "Brazilian Portuguese".to_owned(),
+        }
+    }
+
+    fn pt_br(&self) -> String {
+        match self {
+            Self::InvalidLabel => {
+                "rótulo inválido; talvez você tenha usado caracteres não ASCII ou começado com um número".to_owned()
+            }
+            Self::UnknownInstruction => {
+                "mnemônico não corresponde ao de nenhuma instrução conhecida".to_owned()
+            }
+            Self::UnknownPseudoInstruction => {
+                "mnemônico não corresponde ao de nenhuma pseudoinstrução conhecida".to_owned()
+            }
+            Self::UnknownMneumonic(word) => format!("mnemônico desconhecido `{word}`"),
+            Self::ExpectedMneumonic => "esperava-se um mnemônico".to_owned(),
+            Self::InvalidImmediate => "não foi possível ler o imediato numérico".to_owned(),
+            Self::InvalidAddressProperties => "propriedades de endereço inválidas".to_owned(),
+            Self::ParseError(kind) => format!("erro de sintaxe {kind}"),
+            Self::UnexpectedCharacter(c) => format!("caractere inesperado '{c}'"),
+            Self::DidYouMean(suggestion) => format!("você quis dizer `{suggestion}`?"),
+            Self::NumericOperandOnImportExport => {
+                "operando numérico não pode ser importado nem exportado".to_owned()
+            }
+            Self::SymbolicOperandOnPositional => {
+                "operando simbólico não pode ser usado para reservar endereços ou definir posições"
+                    .to_owned()
+            }
+            Self::UndefinedLabel => "rótulo indefinido usado como operando".to_owned(),
+            Self::AddressOutsideMemory => "endereço fora da memória".to_owned(),
+            Self::ConstantTooWide => {
+                "imediato com mais de 16 bits na pseudoinstrução de constante".to_owned()
+            }
+            Self::ImmediateTooWide => "imediato não pode ter mais de 12 bits".to_owned(),
+            Self::InvalidImportedOperand => {
+                "não é possível substituir operando numérico pela posição".to_owned()
+            }
+            Self::OperandNotImported => "operando marcado como importado, mas não importado".to_owned(),
+            Self::DuplicateExport(symbol) => {
+                format!("símbolo `{symbol}` é exportado mais de uma vez")
+            }
+            Self::MalformedArchive => "arquivo de biblioteca malformado".to_owned(),
+            Self::ArchiveMemberNotFound(member) => {
+                format!("arquivo de biblioteca não possui o membro `{member}`")
+            }
+            Self::UnsafeMemberName(member) => {
+                format!("o nome do membro `{member}` deve ser um nome de arquivo sem diretórios")
+            }
+            Self::ExpectedRegion => "entradas devem ser listadas sob uma região".to_owned(),
+            Self::InvalidRegion => {
+                "esperava-se `REGION <nome> <início> <fim>`, opcionalmente seguido de `ALIGN <alinhamento>`"
+                    .to_owned()
+            }
+            Self::RegionEndsBeforeStart => "região termina antes de começar".to_owned(),
+            Self::UnknownInput(input) => format!("nenhuma entrada chamada `{input}` foi passada"),
+            Self::InputDoesNotFit(input, region) => {
+                format!("`{input}` não cabe na região `{region}`")
+            }
+            Self::RegionOverlaps(region) => format!("região se sobrepõe à região `{region}`"),
+            Self::RegionDeclaredHere => "região declarada aqui".to_owned(),
+            Self::UnexpectedWord(word) => format!("`{word}` inesperado"),
+            Self::MalformedHexRecord => "registro Intel HEX malformado".to_owned(),
+            Self::HexChecksumMismatch => {
+                "checksum do registro Intel HEX não confere".to_owned()
+            }
+            Self::UnsupportedHexRecord(kind) => {
+                format!("tipo de registro Intel HEX {kind:02X} não suportado")
+            }
+            Self::MalformedObject(error) => format!("arquivo objeto malformado: {error}"),
+            Self::ObjectValueTooLarge(field, value, max) => {
+                format!("`{field}` vale {value}, mas não pode passar de /{max:X}")
+            }
+            Self::NotAbsoluteOnConversion(position) => {
+                format!("palavra em /{position:03X} não é absoluta")
+            }
+            Self::RelocateBeforeConverting => {
+                "reloque o programa antes de convertê-lo".to_owned()
+            }
+            Self::WordOutsideMemory(position) => {
+                format!("palavra posicionada em /{position:03X}, além do fim da memória")
+            }
+            Self::OperandOutsideMemory(operand) => {
+                format!("operando relocado para /{operand:03X}, além do fim da memória")
+            }
+            Self::OverlappingWords(position) => {
+                format!("palavra posicionada em /{position:03X} se sobrepõe a outra palavra")
+            }
+            Self::OtherWordPlacedHere => "outra palavra posicionada aqui".to_owned(),
+            Self::UnresolvedSymbols(symbols) => {
+                format!("ligação completa falhou por símbolos não resolvidos: {symbols}")
+            }
+            Self::SymbolImportedHere(symbol) => format!("`{symbol}` importado aqui"),
+            Self::UndefinedReference(symbol) => format!("referência indefinida a `{symbol}`"),
+            Self::UndefinedReferenceAt(symbol, location) => {
+                format!("referência indefinida a {symbol} em {location}")
+            }
+            Self::InvalidRename(rename) => {
+                format!("esperado `ANTIGO=NOVO` com nomes de símbolo válidos, encontrado `{rename}`")
+            }
+            Self::InvalidReservedRange(range) => {
+                format!("esperado `INICIO-FIM` com endereços até /FFF, encontrado `{range}`")
+            }
+            Self::CodeRelocatedTo(base) => format!("código relocado para a base /{base:03X}"),
+            Self::DataRelocatedTo(base) => format!("dados relocados para a base /{base:03X}"),
+            Self::NoRoomForProgram(size) => {
+                format!("não há espaço na memória para /{size:03X} endereços de código relocável")
+            }
+            Self::SegmentInReservedRange(base, start, end) => format!(
+                "segmento relocado para a base /{base:03X} se sobrepõe ao intervalo reservado /{start:03X}-/{end:03X}"
+            ),
+            Self::SymbolNameConflict(symbol) => {
+                format!("renomeação faz mais de um símbolo se chamar `{symbol}`")
+            }
+            Self::ImportsOnRelocation(symbols) => {
+                format!("programa ainda importa símbolos não resolvidos: {symbols}")
+            }
+            Self::LinkBeforeRelocating => {
+                "ligue o programa com `--complete` antes de relocá-lo".to_owned()
+            }
+            Self::HoverLabel(label, position) => format!("`{label}` em /{position:03X}"),
+            Self::HoverImport(label, index) => {
+                format!("`{label}` importado como símbolo {index}")
+            }
+            Self::HoverRelocatable => "relocável".to_owned(),
+            Self::HoverExported => "exportado".to_owned(),
+            Self::HoverWord(word) => format!("montado como `{word}`"),
+            Self::NotFormatted(file) => format!("`{file}` não está formatado"),
+            Self::ErrorWhileHandlingInput => "erro ao processar arquivo de entrada".to_owned(),
+            Self::AbortingDueToErrors(1) => "abortando devido a 1 erro anterior".to_owned(),
+            Self::AbortingDueToErrors(count) => {
+                format!("abortando devido a {count} erros anteriores")
+            }
+            Self::ExplainThisError(code) => {
+                format!("para mais informações sobre este erro, tente `mvn-cli explain {code}`")
+            }
+            Self::SomeErrorsExplained(codes) => {
+                format!("alguns erros possuem explicações detalhadas: {codes}")
+            }
+            Self::ExplainAnError(code) => {
+                format!("para mais informações sobre um erro, tente `mvn-cli explain {code}`")
+            }
+            Self::UnknownErrorCode(code) => format!("`{code}` não é um código de erro conhecido"),
+            Self::InputFileNotFound => "arquivo de entrada não existe".to_owned(),
+            Self::HelpAssemble => "Monta um programa em código intermediário".to_owned(),
+            Self::HelpLink => "Liga programas em código intermediário".to_owned(),
+            Self::HelpRelocate => "Reloca um programa ligado para uma base absoluta".to_owned(),
+            Self::HelpExplain => "Explica um código de erro em detalhes".to_owned(),
+            Self::HelpFmt => "Alinha as colunas de códigos-fonte em linguagem de montagem".to_owned(),
+            Self::HelpCheck => {
+                "Somente aponta os códigos-fonte não formatados, falhando se houver algum"
+                    .to_owned()
+            }
+            Self::HelpSources => {
+                "Códigos-fonte em linguagem de montagem, formatados no lugar".to_owned()
+            }
+            Self::HelpLabelWidth => "Largura da coluna de rótulos".to_owned(),
+            Self::HelpMneumonicWidth => "Largura da coluna de mnemônicos".to_owned(),
+            Self::HelpOperandWidth => {
+                "Largura da coluna de operandos, antes dos comentários".to_owned()
+            }
+            Self::HelpAr => "Cria, lista ou extrai bibliotecas de código intermediário".to_owned(),
+            Self::HelpArCreate => "Cria uma biblioteca a partir de programas em código intermediário".to_owned(),
+            Self::HelpArList => "Lista os membros de uma biblioteca e os símbolos que exportam".to_owned(),
+            Self::HelpArExtract => "Extrai membros de uma biblioteca para o diretório atual".to_owned(),
+            Self::HelpArchive => "Arquivo de biblioteca".to_owned(),
+            Self::HelpMembers => "Membros da biblioteca".to_owned(),
+            Self::HelpLibraries => "Bibliotecas das quais somente os membros necessários são ligados".to_owned(),
+            Self::HelpMap => {
+                "Escreve onde cada entrada e símbolo foram parar em um arquivo de mapa".to_owned()
+            }
+            Self::HelpScript => {
+                "Script de ligação que posiciona as entradas em endereços dados".to_owned()
+            }
+            Self::HelpGc => {
+                "Remove código e dados inalcançáveis a partir do ponto de entrada ou das exportações"
+                    .to_owned()
+            }
+            Self::HelpExport => "Mantém somente as exportações dadas, ocultando as demais".to_owned(),
+            Self::HelpDebug => {
+                "Acompanha cada palavra da linha de código-fonte que a gerou".to_owned()
+            }
+            Self::HelpConvert => {
+                "Converte um programa absoluto em imagem de memória, ou Intel HEX de volta em programa"
+                    .to_owned()
+            }
+            Self::HelpFormat => "Formato da saída".to_owned(),
+            Self::HelpFormatMvn => "Texto MVN, um endereço e uma palavra por linha".to_owned(),
+            Self::HelpDialect => "Formato do programa ligado".to_owned(),
+            Self::HelpFormatJson => {
+                "Objeto JSON com a tabela de símbolos e as palavras".to_owned()
+            }
+            Self::HelpFormatHex => "Registros Intel HEX".to_owned(),
+            Self::HelpFormatBin => {
+                "Bytes a partir do endereço 0, o mais significativo primeiro".to_owned()
+            }
+            Self::HelpFormatVerilog => "Entrada para o $readmemh do Verilog".to_owned(),
+            Self::HelpFormatVhdl => "Constante VHDL que inicializa uma memória".to_owned(),
+            Self::HelpFill => "Byte que preenche lacunas em imagens binárias e VHDL".to_owned(),
+            Self::HelpObjdump => {
+                "Descreve cada linha de um programa em código intermediário, ligado ou de máquina"
+                    .to_owned()
+            }
+            Self::HelpHide => "Oculta as exportações dadas de ligações posteriores".to_owned(),
+            Self::HelpRename => {
+                "Renomeia um símbolo exportado ou importado, como em ANTIGO=NOVO".to_owned()
+            }
+            Self::HelpInput => "Arquivo de entrada".to_owned(),
+            Self::HelpInputOrStdin => {
+                "Arquivo de entrada, lido da entrada padrão se omitido".to_owned()
+            }
+            Self::HelpInputs => "Arquivos de entrada, na ordem de ligação".to_owned(),
+            Self::HelpPartial => {
+                "Mantém importações não resolvidas para uma ligação posterior".to_owned()
+            }
+            Self::HelpComplete => "Exige que todas as importações sejam resolvidas".to_owned(),
+            Self::HelpKeepExports => {
+                "Mantém os símbolos exportados como anotações no programa relocado".to_owned()
+            }
+            Self::HelpSymbols => {
+                "Escreve o endereço de cada símbolo exportado em um arquivo".to_owned()
+            }
+            Self::HelpBase => {
+                "Endereço base do programa relocado, o menor livre se omitido".to_owned()
+            }
+            Self::HelpData => "Endereço em que o segmento de dados começa no programa".to_owned(),
+            Self::HelpDataBase => {
+                "Endereço base do segmento de dados, o menor livre se omitido".to_owned()
+            }
+            Self::HelpReserve => {
+                "Intervalo de endereços, como 0x000-0x0FF, evitado nas bases automáticas".to_owned()
+            }
+            Self::HelpCode => "Código de erro, como E0203".to_owned(),
+            Self::HelpMessageFormat => "Formato dos diagnósticos".to_owned(),
+            Self::HelpMessageFormatHuman => {
+                "Trechos anotados do código, feitos para serem lidos por pessoas".to_owned()
+            }
+            Self::HelpMessageFormatJson => {
+                "Um objeto JSON por linha para cada diagnóstico".to_owned()
+            }
+            Self::HelpLang => "Idioma das mensagens".to_owned(),
+            Self::HelpLangEn => "Inglês".to_owned(),
+            Self::HelpLangPtBr => "Português brasileiro".to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.translate(lang()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_resolve_locale() {
+        assert_eq!(Lang::from_locale("pt_BR.UTF-8"), Some(Lang::PtBr));
+        assert_eq!(Lang::from_locale("pt"), Some(Lang::PtBr));
+        assert_eq!(Lang::from_locale("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("C"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("de_DE"), None);
+    }
+
+    #[test]
+    fn flag_should_take_precedence_over_locale() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let english = |variable: &str| (variable == "LANG").then(|| "en_US.UTF-8".to_owned());
+        let portuguese = |variable: &str| (variable == "LANG").then(|| "pt_BR.UTF-8".to_owned());
+        assert_eq!(Lang::detect_with(args(&["mvn-cli"]), english), Lang::En);
+        assert_eq!(
+            Lang::detect_with(args(&["mvn-cli"]), portuguese),
+            Lang::PtBr
+        );
+        assert_eq!(
+            Lang::detect_with(args(&["mvn-cli", "--lang", "pt-br"]), english),
+            Lang::PtBr
+        );
+        assert_eq!(
+            Lang::detect_with(args(&["mvn-cli", "--lang=pt_BR"]), english),
+            Lang::PtBr
+        );
+        assert_eq!(
+            Lang::detect_with(args(&["mvn-cli", "--lang", "en"]), portuguese),
+            Lang::En
+        );
+    }
+
+    #[test]
+    fn should_translate_summary() {
+        assert_eq!(
+            Message::AbortingDueToErrors(2).translate(Lang::En),
+            "aborting due to 2 previous errors"
+        );
+        assert_eq!(
+            Message::AbortingDueToErrors(1).translate(Lang::PtBr),
+            "abortando devido a 1 erro anterior"
+        );
+    }
+}
@@ -1,14 +1,18 @@
 use std::fs;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 
-pub fn file_exists(path: &str) -> Result<PathBuf, &'static str> {
+use crate::i18n::Message;
+use crate::types::{Position, SourceMap, SourceSpan};
+
+pub fn file_exists(path: &str) -> Result<PathBuf, String> {
     let path = Path::new(path);
     if let Ok(exists) = path.try_exists() {
         if exists {
             return Ok(path.to_path_buf());
         }
     }
-    Err("input file does not exist")
+    Err(Message::InputFileNotFound.to_string())
 }
 
 pub fn read_to_string(path: &PathBuf) -> String {
@@ -16,3 +20,62 @@ pub fn read_to_string(path: &PathBuf) -> String {
         .expect("failed to read file")
         .to_uppercase()
 }
+
+/// Name and content of the program at `path` or, without one, read from stdin
+pub fn read_input(path: Option<&PathBuf>) -> (String, String) {
+    let (name, program) = read_input_verbatim(path);
+    (name, program.to_uppercase())
+}
+
+// Unlike `read_input`, keeps the case of the program
+pub fn read_input_verbatim(path: Option<&PathBuf>) -> (String, String) {
+    match path {
+        Some(path) => (path.display().to_string(), read_verbatim(path)),
+        None => {
+            let mut program = String::new();
+            io::stdin()
+                .read_to_string(&mut program)
+                .expect("unable to read from stdin");
+            ("<stdin>".to_owned(), program)
+        }
+    }
+}
+
+// Unlike `read_to_string`, keeps the case of files that are not programs
+pub fn read_verbatim(path: &PathBuf) -> String {
+    fs::read_to_string(path).expect("failed to read file")
+}
+
+/// Program text along with the name used to refer to it in diagnostics
+#[derive(Clone, Copy, Debug)]
+pub struct Source<'a> {
+    pub name: &'a str,
+    pub content: &'a str,
+    /// Where the content came from, when rewritten from what was read
+    pub map: Option<&'a SourceMap>,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(name: &'a str, content: &'a str) -> Self {
+        Self {
+            name,
+            content,
+            map: None,
+        }
+    }
+
+    pub fn with_map(self, map: &'a SourceMap) -> Self {
+        Self {
+            map: Some(map),
+            ..self
+        }
+    }
+
+    /// Line of what was read, before any rewrite, holding `position` of the content
+    pub fn original_line(&self, position: Position) -> u32 {
+        match self.map {
+            Some(map) => map.locate(position, SourceSpan::default()).0.line,
+            None => position.line,
+        }
+    }
+}
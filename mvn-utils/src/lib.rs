@@ -1,5 +1,10 @@
+pub mod code;
 pub mod error;
+pub mod i18n;
 pub mod io;
+pub mod report;
+pub mod suggestion;
+pub mod table;
 pub mod types;
 
 pub trait Executor {
@@ -21,7 +26,7 @@ use num_traits::Num;
 /*
  * Adapted from https://github.com/Geal/nom/blob/main/doc/nom_recipes.md#hexadecimal
  */
-pub fn hexadecimal<T: Num>(input: Span) -> LocatedIResult<'_, T> {
+pub fn hexadecimal<T: Num>(input: Span<'_>) -> LocatedIResult<'_, T> {
     map(
         recognize(hex_digit1),
         /* Unwrapping is not allowed since `<T as Num>::FromStrRadixErr`
@@ -33,7 +38,7 @@ pub fn hexadecimal<T: Num>(input: Span) -> LocatedIResult<'_, T> {
     )(input)
 }
 
-pub fn ascii(input: Span) -> LocatedIResult<'_, u32> {
+pub fn ascii(input: Span<'_>) -> LocatedIResult<'_, u32> {
     let (rest, bytes) = map(
         // ASCII immediates may contain at most two bytes
         // `many_m_n` ensures there are either 1 or 2 bytes
@@ -72,7 +77,7 @@ pub fn hex_char_to_u8(string: &str) -> u8 {
     }
 }
 
-pub fn comment_or_space(input: error::Span) -> error::LocatedIResult<Option<Span>> {
+pub fn comment_or_space(input: error::Span<'_>) -> error::LocatedIResult<'_, Option<Span<'_>>> {
     let (rest, matched) = preceded(
         space0,
         opt(preceded(tuple((char(';'), space0)), not_line_ending)),
@@ -80,7 +85,7 @@ pub fn comment_or_space(input: error::Span) -> error::LocatedIResult<Option<Span
     Ok((rest, matched))
 }
 
-pub fn ignorable(input: Span) -> LocatedIResult<()> {
+pub fn ignorable(input: Span<'_>) -> LocatedIResult<'_, ()> {
     value((), many0(pair(comment_or_space, line_ending)))(input)
 }
 
@@ -0,0 +1,426 @@
+use annotate_snippets::{
+    display_list::{DisplayList, FormatOptions},
+    snippet::{Annotation, AnnotationType, Slice, Snippet, SourceAnnotation},
+};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::code::ErrorCode;
+use crate::error::{MvnReportError, Severity};
+use crate::i18n::Message;
+use crate::io::Source;
+use crate::types::Position;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MessageFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+// Implemented by hand so that the help of each value is translated
+impl ValueEnum for MessageFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Human, Self::Json]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Human => {
+                PossibleValue::new("human").help(Message::HelpMessageFormatHuman.to_string())
+            }
+            Self::Json => {
+                PossibleValue::new("json").help(Message::HelpMessageFormatJson.to_string())
+            }
+        })
+    }
+}
+
+pub fn print_errors(errors: &[MvnReportError], sources: &[Source], format: MessageFormat) {
+    for error in errors {
+        let rendered = match format {
+            MessageFormat::Human => render_human(error, sources),
+            MessageFormat::Json => render_json(error, sources),
+        };
+        eprintln!("{rendered}");
+    }
+    if format == MessageFormat::Human && !errors.is_empty() {
+        eprintln!("{}", render_summary(errors.len()));
+        if let Some(hint) = render_explain_hint(errors) {
+            eprintln!("{hint}");
+        }
+    }
+}
+
+/// Reports the errors and fails, so that scripts can tell failure apart without reading them
+pub fn exit_with_errors(errors: &[MvnReportError], sources: &[Source], format: MessageFormat) -> ! {
+    print_errors(errors, sources, format);
+    std::process::exit(1)
+}
+
+pub fn render_human(error: &MvnReportError, sources: &[Source]) -> String {
+    let file = resolve_file(error.file.as_deref(), sources);
+    let message = error.message.clone().unwrap_or_default();
+    let annotation_type = match error.severity {
+        Severity::Error => AnnotationType::Error,
+        Severity::Warning => AnnotationType::Warning,
+    };
+
+    let mut annotations = vec![(
+        file,
+        error.position,
+        error.span.len(),
+        message.as_str(),
+        annotation_type,
+    )];
+    for label in error.labels.iter() {
+        let label_file = label.file.as_deref().or(file);
+        annotations.push((
+            label_file,
+            label.position,
+            label.span.len(),
+            label.message.as_str(),
+            AnnotationType::Info,
+        ));
+    }
+
+    // Annotations are grouped by file, keeping the error's file first
+    let mut files: Vec<Option<&str>> = Vec::new();
+    for (file, ..) in annotations.iter() {
+        if !files.contains(file) {
+            files.push(*file);
+        }
+    }
+    let excerpts: Vec<Excerpt> = files
+        .into_iter()
+        .filter_map(|file| {
+            let content = find_source(file, sources)?;
+            let positions = annotations
+                .iter()
+                .filter(|(annotation_file, ..)| *annotation_file == file)
+                .map(|(_, position, length, label, annotation_type)| {
+                    (*position, *length, *label, *annotation_type)
+                });
+            Some(Excerpt::new(file, content, positions))
+        })
+        .collect();
+
+    let title = Message::ErrorWhileHandlingInput.to_string();
+    let snippet = Snippet {
+        title: Some(Annotation {
+            label: Some(&title),
+            id: error.code.as_ref().map(ErrorCode::code),
+            annotation_type,
+        }),
+        footer: error
+            .help
+            .iter()
+            .map(|help| Annotation {
+                label: Some(help),
+                id: None,
+                annotation_type: AnnotationType::Help,
+            })
+            .collect(),
+        slices: excerpts.iter().map(Excerpt::slice).collect(),
+        opt: FormatOptions {
+            color: true,
+            ..Default::default()
+        },
+    };
+    DisplayList::from(snippet).to_string()
+}
+
+pub fn render_json(error: &MvnReportError, sources: &[Source]) -> String {
+    let file = resolve_file(error.file.as_deref(), sources);
+    let diagnostic = JsonDiagnostic {
+        severity: error.severity,
+        code: error.code.as_ref().map(ErrorCode::code),
+        message: error.message.as_deref().unwrap_or_default(),
+        file,
+        line: error.position.line,
+        column: error.position.column,
+        span_length: error.span.len(),
+        labels: error
+            .labels
+            .iter()
+            .map(|label| JsonLabel {
+                message: &label.message,
+                file: label.file.as_deref().or(file),
+                line: label.position.line,
+                column: label.position.column,
+                span_length: label.span.len(),
+            })
+            .collect(),
+        help: &error.help,
+    };
+    serde_json::to_string(&diagnostic).expect("diagnostics should always be serializable")
+}
+
+fn render_summary(count: usize) -> String {
+    let label = Message::AbortingDueToErrors(count).to_string();
+    let snippet = Snippet {
+        title: Some(Annotation {
+            label: Some(&label),
+            id: None,
+            annotation_type: AnnotationType::Error,
+        }),
+        footer: vec![],
+        slices: vec![],
+        opt: FormatOptions {
+            color: true,
+            ..Default::default()
+        },
+    };
+    DisplayList::from(snippet).to_string()
+}
+
+fn render_explain_hint(errors: &[MvnReportError]) -> Option<String> {
+    let mut codes: Vec<&str> = errors
+        .iter()
+        .filter_map(|error| error.code.as_ref().map(ErrorCode::code))
+        .collect();
+    codes.sort();
+    codes.dedup();
+    match codes.as_slice() {
+        [] => None,
+        [code] => Some(Message::ExplainThisError(code).to_string()),
+        [first, ..] => Some(format!(
+            "{}\n{}",
+            Message::SomeErrorsExplained(&codes.join(", ")),
+            Message::ExplainAnError(first)
+        )),
+    }
+}
+
+/*
+ * Errors produced while handling a single input don't need to name
+ * it, so they are attributed to the only source available
+ */
+fn resolve_file<'a>(file: Option<&'a str>, sources: &[Source<'a>]) -> Option<&'a str> {
+    match (file, sources) {
+        (Some(file), _) => Some(file),
+        (None, [source]) => Some(source.name),
+        _ => None,
+    }
+}
+
+fn find_source<'a>(file: Option<&str>, sources: &[Source<'a>]) -> Option<&'a str> {
+    match (file, sources) {
+        (Some(file), _) => sources
+            .iter()
+            .find(|source| source.name == file)
+            .map(|source| source.content),
+        (None, [source]) => Some(source.content),
+        _ => None,
+    }
+}
+
+/*
+ * Continuous range of source lines containing every annotation
+ * that refers to a given file
+ */
+struct Excerpt<'a> {
+    origin: Option<&'a str>,
+    source: String,
+    line_start: usize,
+    annotations: Vec<((usize, usize), &'a str, AnnotationType)>,
+}
+
+impl<'a> Excerpt<'a> {
+    fn new(
+        origin: Option<&'a str>,
+        content: &str,
+        positions: impl Iterator<Item = (Position, usize, &'a str, AnnotationType)>,
+    ) -> Self {
+        let positions: Vec<_> = positions.collect();
+        let line_number = |position: &Position| usize::try_from(position.line).unwrap().max(1);
+        let first_line = positions
+            .iter()
+            .map(|p| line_number(&p.0))
+            .min()
+            .unwrap_or(1);
+        let last_line = positions
+            .iter()
+            .map(|p| line_number(&p.0))
+            .max()
+            .unwrap_or(1);
+
+        let mut lines: Vec<String> = (first_line..=last_line)
+            .map(|line| content.lines().nth(line - 1).unwrap_or_default().to_owned())
+            .collect();
+        /*
+         * Empty spans, such as errors at the end of a line or of the
+         * input, are still marked with a single character, so there
+         * must be room past the last character to place the marker
+         */
+        let length = |length: usize| length.max(1);
+        for (position, span_length, ..) in positions.iter() {
+            let line = &mut lines[line_number(position) - first_line];
+            let width = position.column.saturating_sub(1) + length(*span_length);
+            if line.len() < width {
+                line.push_str(&" ".repeat(width - line.len()));
+            }
+        }
+        let annotations = positions
+            .into_iter()
+            .map(|(position, span_length, label, annotation_type)| {
+                let index = line_number(&position) - first_line;
+                let line_offset: usize = lines[..index].iter().map(|line| line.len() + 1).sum();
+                let start = line_offset + position.column.saturating_sub(1);
+                ((start, start + length(span_length)), label, annotation_type)
+            })
+            .collect();
+
+        Self {
+            origin,
+            source: lines.join("\n"),
+            line_start: first_line,
+            annotations,
+        }
+    }
+
+    fn slice(&self) -> Slice<'_> {
+        Slice {
+            source: &self.source,
+            line_start: self.line_start,
+            origin: self.origin,
+            fold: true,
+            annotations: self
+                .annotations
+                .iter()
+                .map(|(range, label, annotation_type)| SourceAnnotation {
+                    label,
+                    annotation_type: *annotation_type,
+                    range: *range,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic<'a> {
+    severity: Severity,
+    code: Option<&'static str>,
+    message: &'a str,
+    file: Option<&'a str>,
+    line: u32,
+    column: usize,
+    span_length: usize,
+    labels: Vec<JsonLabel<'a>>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    help: &'a [String],
+}
+
+#[derive(Serialize)]
+struct JsonLabel<'a> {
+    message: &'a str,
+    file: Option<&'a str>,
+    line: u32,
+    column: usize,
+    span_length: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MvnReportLabel;
+    use crate::types::SourceSpan;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_render_json_diagnostic() {
+        let error = MvnReportError::new(Position::new(2, 5), Some("foo".to_owned()));
+        let sources = [Source::new("main.asm", "JP /0\nLD FOO\n")];
+        assert_eq!(
+            render_json(&error, &sources),
+            r#"{"severity":"error","code":null,"message":"foo","file":"main.asm","line":2,"column":5,"span_length":0,"labels":[]}"#
+        );
+    }
+
+    #[test]
+    fn should_render_json_secondary_labels() {
+        let error = MvnReportError::new(Position::new(1, 1), Some("foo".to_owned()))
+            .with_file("main.int")
+            .with_label(MvnReportLabel::new(
+                Some("lib.int".to_owned()),
+                Position::new(3, 1),
+                "bar".to_owned(),
+            ));
+        assert_eq!(
+            render_json(&error, &[]),
+            r#"{"severity":"error","code":null,"message":"foo","file":"main.int","line":1,"column":1,"span_length":0,"labels":[{"message":"bar","file":"lib.int","line":3,"column":1,"span_length":0}]}"#
+        );
+    }
+
+    #[test]
+    fn should_render_error_code() {
+        let error = MvnReportError::new(Position::new(1, 1), Some("foo".to_owned()))
+            .with_code(ErrorCode::UndefinedLabel);
+        let sources = [Source::new("main.asm", "JP FOO\n")];
+        assert!(render_json(&error, &sources).contains(r#""code":"E0203""#));
+        assert!(render_human(&error, &sources).contains("E0203"));
+    }
+
+    #[test]
+    fn should_hint_every_explained_code() {
+        let error = |code| MvnReportError::new(Position::new(1, 1), None).with_code(code);
+        assert_eq!(
+            render_explain_hint(&[MvnReportError::new(Position::new(1, 1), None)]),
+            None
+        );
+        assert_eq!(
+            render_explain_hint(&[
+                error(ErrorCode::UndefinedLabel),
+                error(ErrorCode::UndefinedLabel)
+            ]),
+            Some("for more information about this error, try `mvn-cli explain E0203`".to_owned())
+        );
+        assert!(render_explain_hint(&[
+            error(ErrorCode::UndefinedLabel),
+            error(ErrorCode::InvalidLabel)
+        ])
+        .unwrap()
+        .starts_with("some errors have detailed explanations: E0101, E0203\n"));
+    }
+
+    #[test]
+    fn should_render_help_notes() {
+        let error = MvnReportError::new(Position::new(1, 1), Some("foo".to_owned()))
+            .with_help("did you mean `JP`?".to_owned());
+        let sources = [Source::new("main.asm", "JJ /0\n")];
+        assert_eq!(
+            render_json(&error, &sources),
+            r#"{"severity":"error","code":null,"message":"foo","file":"main.asm","line":1,"column":1,"span_length":0,"labels":[],"help":["did you mean `JP`?"]}"#
+        );
+        assert!(render_human(&error, &sources).contains("did you mean `JP`?"));
+    }
+
+    #[test]
+    fn should_report_span_length() {
+        let error = MvnReportError::new(Position::new(2, 4), Some("foo".to_owned()))
+            .with_span(SourceSpan::new(9, 12));
+        let sources = [Source::new("main.asm", "JP /0\nLD FOO\n")];
+        assert_eq!(
+            render_json(&error, &sources),
+            r#"{"severity":"error","code":null,"message":"foo","file":"main.asm","line":2,"column":4,"span_length":3,"labels":[]}"#
+        );
+        let rendered = render_human(&error, &sources);
+        assert!(rendered.contains("^^^") && !rendered.contains("^^^^"));
+    }
+
+    #[test]
+    fn should_render_human_diagnostic_on_empty_line() {
+        let error = MvnReportError::new(Position::new(2, 1), Some("foo".to_owned()));
+        let sources = [Source::new("main.asm", "JP /0\n\nLD FOO\n")];
+        assert!(render_human(&error, &sources).contains("foo"));
+    }
+
+    #[test]
+    fn should_render_human_diagnostic_past_end_of_input() {
+        let error = MvnReportError::new(Position::new(3, 1), Some("foo".to_owned()));
+        let sources = [Source::new("main.asm", "JP /0\n")];
+        assert!(render_human(&error, &sources).contains("foo"));
+    }
+}
@@ -0,0 +1,46 @@
+/*
+ * Finds the candidate closest to a misspelled word, for "did you
+ * mean" hints. Case is ignored when measuring the distance, swapped
+ * adjacent letters count as a single edit, and candidates too
+ * different from the word are not suggested
+ */
+pub fn closest<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let threshold = (word.chars().count() / 3).max(1);
+    let word = word.to_uppercase();
+    candidates
+        .into_iter()
+        .filter(|candidate| !candidate.is_empty())
+        .map(|candidate| {
+            let distance = strsim::osa_distance(&word, &candidate.to_uppercase());
+            (distance, candidate)
+        })
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_suggest_closest_candidate() {
+        let candidates = ["JP", "JZ", "JN", "LD"];
+        assert_eq!(closest("JZZ", candidates), Some("JZ"));
+        assert_eq!(closest("ld", candidates), Some("LD"));
+        assert_eq!(closest("RESULTS", ["RESULT", "ONE"]), Some("RESULT"));
+    }
+
+    #[test]
+    fn should_suggest_candidate_with_swapped_letters() {
+        assert_eq!(closest("VALEU", ["VALUE", "RESULT"]), Some("VALUE"));
+        assert_eq!(closest("LV", ["VL"]), Some("VL"));
+    }
+
+    #[test]
+    fn should_not_suggest_distant_candidate() {
+        assert_eq!(closest("XX", ["JP", "JZ"]), None);
+        assert_eq!(closest("FOO", ["RESULT"]), None);
+    }
+}
@@ -0,0 +1,18 @@
+use std::fmt::Write;
+
+/// Titled table with columns padded to their widest cell, except for the last one
+pub fn render(title: &str, header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let header: Vec<String> = header.iter().map(|cell| cell.to_string()).collect();
+    let rows: Vec<Vec<String>> = std::iter::once(header).chain(rows).collect();
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+        .collect();
+    let mut table = format!("{title}\n");
+    for row in rows {
+        let cells: Vec<String> = std::iter::zip(&row, &widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        writeln!(table, "  {}", cells.join("  ").trim_end()).unwrap();
+    }
+    table
+}
@@ -26,15 +26,67 @@ impl From<Span<'_>> for Position {
     }
 }
 
-#[derive(Debug, PartialEq)]
+/*
+ * Byte offsets delimiting a token in the input it was parsed
+ * from, with `end` pointing right past its last character
+ */
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct SourceSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl SourceSpan {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn between(start: Span<'_>, end: Span<'_>) -> Self {
+        Self::new(start.location_offset(), end.location_offset())
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<Span<'_>> for SourceSpan {
+    fn from(value: Span<'_>) -> Self {
+        let start = value.location_offset();
+        Self::new(start, start + value.fragment().len())
+    }
+}
+
+#[derive(Debug)]
 pub struct Token<T> {
     pub position: Position,
+    pub span: SourceSpan,
     pub value: T,
 }
 
 impl<T> Token<T> {
     pub fn new(position: Position, value: T) -> Self {
-        Self { position, value }
+        Self {
+            position,
+            span: SourceSpan::default(),
+            value,
+        }
+    }
+
+    pub fn with_span(self, span: SourceSpan) -> Self {
+        Self { span, ..self }
+    }
+}
+
+// A token's extent follows from where it starts and what it
+// holds, so the span is left out when comparing tokens
+impl<T: PartialEq> PartialEq for Token<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position && self.value == other.value
     }
 }
 
@@ -43,6 +95,7 @@ impl<T> From<T> for Token<T> {
         Self {
             value,
             position: Position::default(),
+            span: SourceSpan::default(),
         }
     }
 }
@@ -64,3 +117,77 @@ impl<T: fmt::UpperHex> fmt::UpperHex for Token<T> {
         write!(f, "{:X}", &self.value)
     }
 }
+
+/*
+ * Where the text of a rewritten input came from, such as a program
+ * read as JSON, so that whatever is found in the rewrite can be pointed
+ * back to the original. Text added by the rewrite points to the field
+ * right after it
+ */
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SourceMap {
+    // Fields of the rewrite, as their offset and length, and where they came from
+    fields: Vec<(usize, usize, (usize, usize))>,
+    lines: Vec<usize>,
+    original_lines: Vec<usize>,
+}
+
+impl SourceMap {
+    pub fn new(original: &str) -> Self {
+        let ends = original.match_indices('\n').map(|(offset, _)| offset + 1);
+        Self {
+            fields: Vec::new(),
+            lines: Vec::new(),
+            original_lines: std::iter::once(0).chain(ends).collect(),
+        }
+    }
+
+    /// Marks a line of the rewrite as starting at `offset`
+    pub fn push_line(&mut self, offset: usize) {
+        self.lines.push(offset);
+    }
+
+    /// Marks `length` bytes at `offset` in the rewrite as written in place of
+    /// the `original` range, so that anything found in them points to it
+    pub fn push_field(&mut self, offset: usize, length: usize, original: (usize, usize)) {
+        self.fields.push((offset, length, original));
+    }
+
+    /// Place in the original of something found in the rewrite
+    pub fn locate(&self, position: Position, span: SourceSpan) -> (Position, SourceSpan) {
+        let line_start = self
+            .lines
+            .get(position.line.saturating_sub(1) as usize)
+            .or(self.lines.last())
+            .copied()
+            .unwrap_or_default();
+        let start = self.to_original(line_start + position.column.saturating_sub(1));
+        let span = match span.is_empty() {
+            true => SourceSpan::new(self.to_original(span.start), self.to_original(span.start)),
+            false => SourceSpan::new(
+                self.to_original(span.start),
+                self.to_original(span.end - 1) + 1,
+            ),
+        };
+        let line = self.original_lines.partition_point(|&line| line <= start);
+        let column = start - self.original_lines[line - 1] + 1;
+        (Position::new(line as u32, column), span)
+    }
+
+    fn to_original(&self, offset: usize) -> usize {
+        let index = self
+            .fields
+            .partition_point(|&(start, length, _)| start + length <= offset);
+        match self.fields.get(index).or(self.fields.last()) {
+            // The last byte of a field stands for the last one of what it replaced
+            Some(&(start, length, (original, original_end))) => {
+                let last = original_end.saturating_sub(1).max(original);
+                match offset + 1 >= start + length {
+                    true => last,
+                    false => (original + offset.saturating_sub(start)).min(last),
+                }
+            }
+            None => 0,
+        }
+    }
+}